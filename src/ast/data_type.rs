@@ -0,0 +1,189 @@
+use std::fmt::Write;
+
+use super::expr::IntervalUnit;
+use super::unparse::{display_unparsed, SqlWriter, Unparse};
+use super::{Ident, NodeSpan, Span, Spanned};
+
+/// Data types, used by casts and later by column definitions.
+///
+/// Synonyms are parsed to the same type and printed in a canonical spelling,
+/// e.g. `INTEGER` and `INT4` are both `Int` and printed as `INT`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type"))]
+pub enum DataType {
+    /// `TINYINT`
+    TinyInt,
+    /// `SMALLINT`, `INT2`
+    SmallInt,
+    /// `INT`, `INTEGER`, `INT4`
+    Int,
+    /// `BIGINT`, `INT8`
+    BigInt,
+    /// `REAL`, `FLOAT4`
+    Real,
+    /// `DOUBLE [PRECISION]`, `FLOAT8`
+    Double,
+    /// `FLOAT[(precision)]`
+    Float { precision: Option<u64> },
+    /// `DECIMAL[(precision[, scale])]`, `NUMERIC`, `DEC`
+    Decimal {
+        precision: Option<u64>,
+        scale: Option<u64>,
+    },
+    /// `CHAR[(length)]`, `CHARACTER`
+    Char { length: Option<u64> },
+    /// `VARCHAR[(length)]`, `CHARACTER VARYING`
+    Varchar { length: Option<u64> },
+    /// `TEXT`
+    Text,
+    /// `STRING`, the text type of BigQuery and Spark
+    String,
+    /// `BOOLEAN`, `BOOL`
+    Boolean,
+    /// `DATE`
+    Date,
+    /// `TIME[(precision)] [WITH | WITHOUT TIME ZONE]`
+    Time {
+        precision: Option<u64>,
+        with_time_zone: bool,
+    },
+    /// `TIMESTAMP[(precision)] [WITH | WITHOUT TIME ZONE]`, `TIMESTAMPTZ[(precision)]`
+    Timestamp {
+        precision: Option<u64>,
+        with_time_zone: bool,
+    },
+    /// `INTERVAL [unit [TO unit]]`, like `INTERVAL DAY TO SECOND`
+    Interval {
+        unit: Option<IntervalUnit>,
+        to: Option<IntervalUnit>,
+    },
+    /// `ARRAY<element>` or `element[]`
+    Array { element: Box<DataType> },
+    /// `STRUCT<name type, ...>`
+    Struct { fields: Vec<StructField> },
+    /// `MAP<key, value>`
+    Map {
+        key: Box<DataType>,
+        value: Box<DataType>,
+    },
+    /// Any other type name, like a user defined type, optionally qualified by its schema
+    Custom { schema: Option<Ident>, name: Ident },
+}
+impl Unparse for DataType {
+    fn unparse(&self, w: &mut SqlWriter) -> std::fmt::Result {
+        match self {
+            Self::TinyInt => w.keyword("TINYINT"),
+            Self::SmallInt => w.keyword("SMALLINT"),
+            Self::Int => w.keyword("INT"),
+            Self::BigInt => w.keyword("BIGINT"),
+            Self::Real => w.keyword("REAL"),
+            Self::Double => w.keyword("DOUBLE PRECISION"),
+            Self::Float { precision } => {
+                w.keyword("FLOAT")?;
+                if let Some(precision) = precision {
+                    write!(w, "({precision})")?;
+                }
+                Ok(())
+            }
+            Self::Decimal { precision, scale } => {
+                w.keyword("DECIMAL")?;
+                match (precision, scale) {
+                    (Some(precision), Some(scale)) => write!(w, "({precision}, {scale})"),
+                    (Some(precision), None) => write!(w, "({precision})"),
+                    _ => Ok(()),
+                }
+            }
+            Self::Char { length } | Self::Varchar { length } => {
+                match self {
+                    Self::Char { .. } => w.keyword("CHAR")?,
+                    _ => w.keyword("VARCHAR")?,
+                }
+                if let Some(length) = length {
+                    write!(w, "({length})")?;
+                }
+                Ok(())
+            }
+            Self::Text => w.keyword("TEXT"),
+            Self::String => w.keyword("STRING"),
+            Self::Boolean => w.keyword("BOOLEAN"),
+            Self::Date => w.keyword("DATE"),
+            Self::Time {
+                precision,
+                with_time_zone,
+            }
+            | Self::Timestamp {
+                precision,
+                with_time_zone,
+            } => {
+                match self {
+                    Self::Time { .. } => w.keyword("TIME")?,
+                    _ => w.keyword("TIMESTAMP")?,
+                }
+                if let Some(precision) = precision {
+                    write!(w, "({precision})")?;
+                }
+                if *with_time_zone {
+                    w.keyword(" WITH TIME ZONE")?;
+                }
+                Ok(())
+            }
+            Self::Interval { unit, to } => {
+                w.keyword("INTERVAL")?;
+                if let Some(unit) = unit {
+                    w.write_str(" ")?;
+                    unit.unparse(w)?;
+                }
+                if let Some(to) = to {
+                    w.keyword(" TO ")?;
+                    to.unparse(w)?;
+                }
+                Ok(())
+            }
+            Self::Array { element } => {
+                element.unparse(w)?;
+                w.write_str("[]")
+            }
+            Self::Struct { fields } => {
+                w.keyword("STRUCT<")?;
+                w.comma_separated(fields)?;
+                w.write_str(">")
+            }
+            Self::Map { key, value } => {
+                w.keyword("MAP<")?;
+                key.unparse(w)?;
+                w.write_str(", ")?;
+                value.unparse(w)?;
+                w.write_str(">")
+            }
+            Self::Custom { schema, name } => {
+                if let Some(schema) = schema {
+                    write!(w, "{schema}.")?;
+                }
+                write!(w, "{name}")
+            }
+        }
+    }
+}
+
+/// A named field of a `STRUCT` type
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StructField {
+    pub name: Ident,
+    pub data_type: DataType,
+    pub span: NodeSpan,
+}
+impl Spanned for StructField {
+    fn span(&self) -> Span {
+        *self.span
+    }
+}
+impl Unparse for StructField {
+    fn unparse(&self, w: &mut SqlWriter) -> std::fmt::Result {
+        write!(w, "{} ", self.name)?;
+        self.data_type.unparse(w)
+    }
+}
+
+display_unparsed!(DataType, StructField);
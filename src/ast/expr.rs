@@ -1,137 +1,572 @@
-use super::Ident;
+use std::fmt::Write;
+
+use super::unparse::{display_unparsed, SqlWriter, Unparse};
+use super::{Ident, NodeSpan, Span, Spanned};
+use crate::ast::data_type::DataType;
 use crate::ast::statement::{OrderByExpr, SelectStatement};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type"))]
 pub enum Expr {
     ColumnRef {
         database: Option<Ident>,
         table: Option<Ident>,
         column: Ident,
+        span: NodeSpan,
+    },
+    Literal {
+        literal: Literal,
+        span: NodeSpan,
+    },
+    Subquery {
+        subquery: Box<SelectStatement>,
+        span: NodeSpan,
     },
-    Literal(Literal),
-    Subquery(Box<SelectStatement>),
     UnaryOp {
         op: UnaryOp,
         expr: Box<Expr>,
+        span: NodeSpan,
     },
     BinaryOp {
         left: Box<Expr>,
         op: BinaryOp,
         right: Box<Expr>,
+        span: NodeSpan,
     },
+    /// `name([DISTINCT] args [{IGNORE | RESPECT} NULLS] [ORDER BY ..] [LIMIT n])
+    /// [WITHIN GROUP (ORDER BY ..)] [FILTER (WHERE condition)] [OVER window]`
     Function {
         name: Ident,
         distinct: bool,
         args: Vec<FunctionArg>,
-        over: Option<Window>,
+        null_treatment: Option<NullTreatment>,
+        /// Order of the rows an aggregate like `string_agg(a, ',' ORDER BY a)` consumes
+        order_by: Vec<OrderByExpr>,
+        /// Most rows an aggregate like `array_agg(a LIMIT 10)` collects
+        limit: Option<Box<Expr>>,
+        /// Order of the rows an ordered-set aggregate like `percentile_cont` consumes
+        within_group: Vec<OrderByExpr>,
+        /// Only the rows matching the condition are aggregated
+        filter: Option<Box<Expr>>,
+        over: Option<Box<Window>>,
+        span: NodeSpan,
     },
     Exists {
         not: bool,
         subquery: Box<SelectStatement>,
+        span: NodeSpan,
     },
     InSubquery {
         not: bool,
         expr: Box<Expr>,
         subquery: Box<SelectStatement>,
+        span: NodeSpan,
     },
     InList {
         not: bool,
         expr: Box<Expr>,
         list: Vec<Expr>,
+        span: NodeSpan,
+    },
+    /// `CASE [operand] WHEN condition THEN result ... [ELSE else_result] END`,
+    /// with an operand the conditions are values compared to it
+    Case {
+        operand: Option<Box<Expr>>,
+        whens: Vec<CaseWhen>,
+        else_result: Option<Box<Expr>>,
+        span: NodeSpan,
+    },
+    /// `CAST(expr AS data_type)`, one of its variants or `expr::data_type`
+    Cast {
+        kind: CastKind,
+        expr: Box<Expr>,
+        data_type: DataType,
+        span: NodeSpan,
+    },
+    /// `expr IS [NOT] NULL`
+    IsNull {
+        not: bool,
+        expr: Box<Expr>,
+        span: NodeSpan,
+    },
+    /// `expr IS [NOT] TRUE`
+    IsTrue {
+        not: bool,
+        expr: Box<Expr>,
+        span: NodeSpan,
+    },
+    /// `expr IS [NOT] FALSE`
+    IsFalse {
+        not: bool,
+        expr: Box<Expr>,
+        span: NodeSpan,
+    },
+    /// `expr IS [NOT] UNKNOWN`
+    IsUnknown {
+        not: bool,
+        expr: Box<Expr>,
+        span: NodeSpan,
+    },
+    /// `left IS [NOT] DISTINCT FROM right`, a comparison where `NULL` equals `NULL`
+    IsDistinctFrom {
+        not: bool,
+        left: Box<Expr>,
+        right: Box<Expr>,
+        span: NodeSpan,
+    },
+    /// `expr [NOT] BETWEEN [SYMMETRIC] low AND high`, the bounds may be in
+    /// either order if `SYMMETRIC`
+    Between {
+        not: bool,
+        symmetric: bool,
+        expr: Box<Expr>,
+        low: Box<Expr>,
+        high: Box<Expr>,
+        span: NodeSpan,
+    },
+    /// `expr [NOT] LIKE pattern [ESCAPE 'c']`
+    Like {
+        not: bool,
+        expr: Box<Expr>,
+        pattern: Pattern,
+        escape: Option<String>,
+        span: NodeSpan,
+    },
+    /// `expr [NOT] ILIKE pattern [ESCAPE 'c']`, a case insensitive `LIKE`
+    ILike {
+        not: bool,
+        expr: Box<Expr>,
+        pattern: Pattern,
+        escape: Option<String>,
+        span: NodeSpan,
+    },
+    /// `expr [NOT] SIMILAR TO pattern [ESCAPE 'c']`
+    SimilarTo {
+        not: bool,
+        expr: Box<Expr>,
+        pattern: Pattern,
+        escape: Option<String>,
+        span: NodeSpan,
+    },
+    /// `expr [NOT] REGEXP pattern` or its `RLIKE` synonym
+    RLike {
+        regexp: bool,
+        not: bool,
+        expr: Box<Expr>,
+        pattern: Pattern,
+        escape: Option<String>,
+        span: NodeSpan,
+    },
+    /// `INTERVAL '1' DAY`, an interval literal with an optional unit of its value
+    Interval {
+        value: Literal,
+        unit: Option<IntervalUnit>,
+        span: NodeSpan,
+    },
+    /// Placeholder of the skipped tokens of an expression that failed to parse,
+    /// only produced by a recovering parse
+    Error {
+        span: NodeSpan,
     },
 }
-impl std::fmt::Display for Expr {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl Spanned for Expr {
+    fn span(&self) -> Span {
+        match self {
+            Self::ColumnRef { span, .. }
+            | Self::Literal { span, .. }
+            | Self::Subquery { span, .. }
+            | Self::UnaryOp { span, .. }
+            | Self::BinaryOp { span, .. }
+            | Self::Function { span, .. }
+            | Self::Exists { span, .. }
+            | Self::InSubquery { span, .. }
+            | Self::InList { span, .. }
+            | Self::Case { span, .. }
+            | Self::Cast { span, .. }
+            | Self::IsNull { span, .. }
+            | Self::IsTrue { span, .. }
+            | Self::IsFalse { span, .. }
+            | Self::IsUnknown { span, .. }
+            | Self::IsDistinctFrom { span, .. }
+            | Self::Between { span, .. }
+            | Self::Like { span, .. }
+            | Self::ILike { span, .. }
+            | Self::SimilarTo { span, .. }
+            | Self::RLike { span, .. }
+            | Self::Interval { span, .. }
+            | Self::Error { span } => **span,
+        }
+    }
+}
+impl Unparse for Expr {
+    fn unparse(&self, w: &mut SqlWriter) -> std::fmt::Result {
         match self {
             Self::ColumnRef {
                 database,
                 table,
                 column,
+                ..
             } => {
                 if let Some(database) = database {
-                    write!(f, "{}.", database)?;
+                    write!(w, "{}.", database)?;
                 }
                 if let Some(table) = table {
-                    write!(f, "{}.", table)?;
+                    write!(w, "{}.", table)?;
                 }
-                write!(f, "{}", column)
+                write!(w, "{}", column)
+            }
+            Self::Literal { literal, .. } => literal.unparse(w),
+            Self::Subquery { subquery, .. } => {
+                w.write_str("(")?;
+                subquery.unparse(w)?;
+                w.write_str(")")
+            }
+            Self::UnaryOp { op, expr, .. } => {
+                op.unparse(w)?;
+                // `--` would start a comment
+                if *op == UnaryOp::Not || matches!(**expr, Self::UnaryOp { .. }) {
+                    w.write_str(" ")?;
+                }
+                // `NOT EXISTS` would be read back as a single `Exists`
+                let exists = *op == UnaryOp::Not && matches!(**expr, Self::Exists { .. });
+                unparse_operand(
+                    w,
+                    expr,
+                    exists || expr.binding_power() < self.binding_power(),
+                )
+            }
+            Self::BinaryOp {
+                left, op, right, ..
+            } => {
+                // binary operators are left associative
+                unparse_operand(w, left, left.binding_power() < self.binding_power())?;
+                w.write_str(" ")?;
+                op.unparse(w)?;
+                w.write_str(" ")?;
+                unparse_operand(w, right, right.binding_power() <= self.binding_power())
             }
-            Self::Literal(literal) => write!(f, "{}", literal),
-            Self::Subquery(query) => write!(f, "({})", query),
-            Self::UnaryOp { op, expr } => write!(f, "{}{}", op, expr),
-            Self::BinaryOp { left, op, right } => write!(f, "({} {} {})", left, op, right),
             Self::Function {
                 name,
                 distinct,
                 args,
+                null_treatment,
+                order_by,
+                limit,
+                within_group,
+                filter,
                 over,
+                ..
             } => {
-                write!(
-                    f,
-                    "{}({}{})",
-                    name,
-                    if *distinct { "DISTINCT " } else { "" },
-                    args.iter()
-                        .map(|a| a.to_string())
-                        .collect::<Vec<_>>()
-                        .join(", ")
-                )?;
+                write!(w, "{}(", name)?;
+                if *distinct {
+                    w.keyword("DISTINCT ")?;
+                }
+                w.comma_separated(args)?;
+                // the clauses inside the parentheses follow the arguments, if any
+                let mut delim = if args.is_empty() { "" } else { " " };
+                if let Some(null_treatment) = null_treatment {
+                    w.write_str(delim)?;
+                    null_treatment.unparse(w)?;
+                    delim = " ";
+                }
+                if !order_by.is_empty() {
+                    w.write_str(delim)?;
+                    w.keyword("ORDER BY ")?;
+                    w.comma_separated(order_by)?;
+                    delim = " ";
+                }
+                if let Some(limit) = limit {
+                    w.write_str(delim)?;
+                    w.keyword("LIMIT ")?;
+                    limit.unparse(w)?;
+                }
+                w.write_str(")")?;
+                if !within_group.is_empty() {
+                    w.keyword(" WITHIN GROUP (ORDER BY ")?;
+                    w.comma_separated(within_group)?;
+                    w.write_str(")")?;
+                }
+                if let Some(filter) = filter {
+                    w.keyword(" FILTER (WHERE ")?;
+                    filter.unparse(w)?;
+                    w.write_str(")")?;
+                }
                 if let Some(window) = over {
-                    write!(f, " OVER {window}")?;
+                    w.keyword(" OVER ")?;
+                    window.unparse(w)?;
                 }
                 Ok(())
             }
-            Self::Exists { not, subquery } => {
+            Self::Exists { not, subquery, .. } => {
                 if *not {
-                    write!(f, "NOT EXISTS")?;
+                    w.keyword("NOT EXISTS")?;
                 } else {
-                    write!(f, "EXISTS")?;
+                    w.keyword("EXISTS")?;
                 }
-                write!(f, " ({})", subquery)?;
-                Ok(())
+                w.write_str(" (")?;
+                subquery.unparse(w)?;
+                w.write_str(")")
             }
             Self::InSubquery {
                 not,
                 expr,
                 subquery,
+                ..
             } => {
+                unparse_operand(w, expr, expr.binding_power() < self.binding_power())?;
                 if *not {
-                    write!(f, "{} NOT IN", expr)?;
+                    w.keyword(" NOT IN")?;
                 } else {
-                    write!(f, "{} IN", expr)?;
+                    w.keyword(" IN")?;
                 }
-                write!(f, " ({})", subquery)?;
-                Ok(())
+                w.write_str(" (")?;
+                subquery.unparse(w)?;
+                w.write_str(")")
             }
-            Self::InList { not, expr, list } => {
+            Self::InList {
+                not, expr, list, ..
+            } => {
+                unparse_operand(w, expr, expr.binding_power() < self.binding_power())?;
                 if *not {
-                    write!(f, "{} NOT IN", expr)?;
+                    w.keyword(" NOT IN")?;
                 } else {
-                    write!(f, "{} IN", expr)?;
-                }
-                write!(
-                    f,
-                    " ({})",
-                    list.iter()
-                        .map(|e| e.to_string())
-                        .collect::<Vec<_>>()
-                        .join(", ")
+                    w.keyword(" IN")?;
+                }
+                w.write_str(" (")?;
+                w.comma_separated(list)?;
+                w.write_str(")")
+            }
+            Self::Case {
+                operand,
+                whens,
+                else_result,
+                ..
+            } => {
+                w.keyword("CASE")?;
+                if let Some(operand) = operand {
+                    w.write_str(" ")?;
+                    operand.unparse(w)?;
+                }
+                for when in whens {
+                    w.write_str(" ")?;
+                    when.unparse(w)?;
+                }
+                if let Some(else_result) = else_result {
+                    w.keyword(" ELSE ")?;
+                    else_result.unparse(w)?;
+                }
+                w.keyword(" END")
+            }
+            Self::Cast {
+                kind,
+                expr,
+                data_type,
+                ..
+            } => match kind {
+                CastKind::DoubleColon => {
+                    unparse_operand(w, expr, expr.binding_power() < self.binding_power())?;
+                    w.write_str("::")?;
+                    data_type.unparse(w)
+                }
+                _ => {
+                    kind.unparse(w)?;
+                    w.write_str("(")?;
+                    expr.unparse(w)?;
+                    w.keyword(" AS ")?;
+                    data_type.unparse(w)?;
+                    w.write_str(")")
+                }
+            },
+            Self::IsNull { not, expr, .. }
+            | Self::IsTrue { not, expr, .. }
+            | Self::IsFalse { not, expr, .. }
+            | Self::IsUnknown { not, expr, .. } => {
+                unparse_operand(w, expr, expr.binding_power() < self.binding_power())?;
+                w.keyword(" IS ")?;
+                if *not {
+                    w.keyword("NOT ")?;
+                }
+                match self {
+                    Self::IsNull { .. } => w.keyword("NULL"),
+                    Self::IsTrue { .. } => w.keyword("TRUE"),
+                    Self::IsFalse { .. } => w.keyword("FALSE"),
+                    _ => w.keyword("UNKNOWN"),
+                }
+            }
+            Self::IsDistinctFrom {
+                not, left, right, ..
+            } => {
+                unparse_operand(w, left, left.binding_power() < self.binding_power())?;
+                if *not {
+                    w.keyword(" IS NOT DISTINCT FROM ")?;
+                } else {
+                    w.keyword(" IS DISTINCT FROM ")?;
+                }
+                unparse_operand(w, right, right.binding_power() <= self.binding_power())
+            }
+            Self::Between {
+                not,
+                symmetric,
+                expr,
+                low,
+                high,
+                ..
+            } => {
+                unparse_operand(w, expr, expr.binding_power() < self.binding_power())?;
+                if *not {
+                    w.keyword(" NOT")?;
+                }
+                w.keyword(" BETWEEN ")?;
+                if *symmetric {
+                    w.keyword("SYMMETRIC ")?;
+                }
+                unparse_operand(
+                    w,
+                    low,
+                    (!*symmetric && starts_with_symmetric(low))
+                        || low.binding_power() <= self.binding_power(),
                 )?;
+                w.keyword(" AND ")?;
+                unparse_operand(w, high, high.binding_power() <= self.binding_power())
+            }
+            Self::Like {
+                not,
+                expr,
+                pattern,
+                escape,
+                ..
+            }
+            | Self::ILike {
+                not,
+                expr,
+                pattern,
+                escape,
+                ..
+            }
+            | Self::SimilarTo {
+                not,
+                expr,
+                pattern,
+                escape,
+                ..
+            }
+            | Self::RLike {
+                not,
+                expr,
+                pattern,
+                escape,
+                ..
+            } => {
+                unparse_operand(w, expr, expr.binding_power() < self.binding_power())?;
+                if *not {
+                    w.keyword(" NOT")?;
+                }
+                match self {
+                    Self::Like { .. } => w.keyword(" LIKE ")?,
+                    Self::ILike { .. } => w.keyword(" ILIKE ")?,
+                    Self::SimilarTo { .. } => w.keyword(" SIMILAR TO ")?,
+                    Self::RLike { regexp: true, .. } => w.keyword(" REGEXP ")?,
+                    _ => w.keyword(" RLIKE ")?,
+                }
+                match pattern {
+                    Pattern::Expr(pattern) => unparse_operand(
+                        w,
+                        pattern,
+                        pattern.binding_power() <= self.binding_power(),
+                    )?,
+                    _ => pattern.unparse(w)?,
+                }
+                if let Some(escape) = escape {
+                    w.keyword(" ESCAPE ")?;
+                    Literal::String(escape.clone()).unparse(w)?;
+                }
                 Ok(())
             }
+            Self::Interval { value, unit, .. } => {
+                w.keyword("INTERVAL ")?;
+                value.unparse(w)?;
+                if let Some(unit) = unit {
+                    w.write_str(" ")?;
+                    unit.unparse(w)?;
+                }
+                Ok(())
+            }
+            Self::Error { .. } => w.write_str("<error>"),
         }
     }
 }
 
+/// Whether the sql of the expr starts with a `symmetric` column or function, which
+/// would read back as the `SYMMETRIC` of a `BETWEEN` if it is the low bound.
+fn starts_with_symmetric(expr: &Expr) -> bool {
+    let first = match expr {
+        Expr::ColumnRef {
+            database: None,
+            table: None,
+            column: name,
+            ..
+        }
+        | Expr::Function { name, .. } => return name.value.eq_ignore_ascii_case("symmetric"),
+        Expr::BinaryOp { left: first, .. }
+        | Expr::IsDistinctFrom { left: first, .. }
+        | Expr::InSubquery { expr: first, .. }
+        | Expr::InList { expr: first, .. }
+        | Expr::IsNull { expr: first, .. }
+        | Expr::IsTrue { expr: first, .. }
+        | Expr::IsFalse { expr: first, .. }
+        | Expr::IsUnknown { expr: first, .. }
+        | Expr::Between { expr: first, .. }
+        | Expr::Like { expr: first, .. }
+        | Expr::ILike { expr: first, .. }
+        | Expr::SimilarTo { expr: first, .. }
+        | Expr::RLike { expr: first, .. }
+        | Expr::Cast {
+            kind: CastKind::DoubleColon,
+            expr: first,
+            ..
+        } => first,
+        _ => return false,
+    };
+    // a first operand in parentheses starts with `(`
+    first.binding_power() >= expr.binding_power() && starts_with_symmetric(first)
+}
+
+/// Writes an operand of an operator, in parentheses if it binds looser than the operator.
+fn unparse_operand(w: &mut SqlWriter, expr: &Expr, parens: bool) -> std::fmt::Result {
+    if parens {
+        w.write_str("(")?;
+        expr.unparse(w)?;
+        w.write_str(")")
+    } else {
+        expr.unparse(w)
+    }
+}
+
 /// Binary operators
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum BinaryOp {
-    // + - * / %
+    // + - * / % // **
     Add,
     Sub,
     Mul,
     Div,
     Mod,
+    IntDiv,
+    Exponent,
+    /// `^`, its meaning depends on the dialect: an exponent in PostgreSQL and a
+    /// bitwise xor in MySQL. Both bind it tighter than `*`.
+    Caret,
+
+    // ||
+    StringConcat,
+
+    // & | << >>
+    BitwiseAnd,
+    BitwiseOr,
+    ShiftLeft,
+    ShiftRight,
 
     // > < >= <= = !=
     Gt,
@@ -145,121 +580,580 @@ pub enum BinaryOp {
     And,
     Or,
 }
-impl std::fmt::Display for BinaryOp {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl Unparse for BinaryOp {
+    fn unparse(&self, w: &mut SqlWriter) -> std::fmt::Result {
         match self {
-            Self::Add => write!(f, "+"),
-            Self::Sub => write!(f, "-"),
-            Self::Mul => write!(f, "*"),
-            Self::Div => write!(f, "/"),
-            Self::Mod => write!(f, "%"),
-            Self::Gt => write!(f, ">"),
-            Self::Lt => write!(f, "<"),
-            Self::GtEq => write!(f, ">="),
-            Self::LtEq => write!(f, "<="),
-            Self::Eq => write!(f, "="),
-            Self::NotEq => write!(f, "!="),
-            Self::And => write!(f, "AND"),
-            Self::Or => write!(f, "OR"),
+            Self::Add => w.keyword("+"),
+            Self::Sub => w.keyword("-"),
+            Self::Mul => w.keyword("*"),
+            Self::Div => w.keyword("/"),
+            Self::Mod => w.keyword("%"),
+            Self::IntDiv => w.keyword("//"),
+            Self::Exponent => w.keyword("**"),
+            Self::Caret => w.keyword("^"),
+            Self::StringConcat => w.keyword("||"),
+            Self::BitwiseAnd => w.keyword("&"),
+            Self::BitwiseOr => w.keyword("|"),
+            Self::ShiftLeft => w.keyword("<<"),
+            Self::ShiftRight => w.keyword(">>"),
+            Self::Gt => w.keyword(">"),
+            Self::Lt => w.keyword("<"),
+            Self::GtEq => w.keyword(">="),
+            Self::LtEq => w.keyword("<="),
+            Self::Eq => w.keyword("="),
+            Self::NotEq => w.keyword("!="),
+            Self::And => w.keyword("AND"),
+            Self::Or => w.keyword("OR"),
         }
     }
 }
 
 /// Literal values
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "value"))]
 pub enum Literal {
     String(String),
-    UnsignedInteger(usize),
-    UnsignedFloat(f64),
+    Number(Number),
+    Boolean(bool),
+    Null,
+}
+impl Unparse for Literal {
+    fn unparse(&self, w: &mut SqlWriter) -> std::fmt::Result {
+        match self {
+            Self::String(s) => {
+                write!(w, "'")?;
+                for c in s.chars() {
+                    match c {
+                        '\'' => write!(w, "''")?,
+                        '\\' => write!(w, "\\\\")?,
+                        '\n' => write!(w, "\\n")?,
+                        '\r' => write!(w, "\\r")?,
+                        '\t' => write!(w, "\\t")?,
+                        '\0' => write!(w, "\\0")?,
+                        '\u{8}' => write!(w, "\\b")?,
+                        c => write!(w, "{}", c)?,
+                    }
+                }
+                write!(w, "'")
+            }
+            Self::Number(n) => write!(w, "{}", n),
+            Self::Boolean(true) => w.keyword("TRUE"),
+            Self::Boolean(false) => w.keyword("FALSE"),
+            Self::Null => w.keyword("NULL"),
+        }
+    }
+}
+
+/// An unsigned numeric literal, kept as written so that no digits are lost.
+///
+/// Besides integers, decimals and exponents, this covers the `0x`, `0o` and `0b`
+/// prefixed integers and the `_` digit separators of PostgreSQL 16. Values are
+/// only converted on demand, a minus sign in front of a literal is a unary operator.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct Number(String);
+impl Number {
+    pub fn new(text: impl Into<String>) -> Self {
+        Number(text.into())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    // The radix and the digits after its prefix
+    fn radix(&self) -> (u32, &str) {
+        let prefix = self.0.get(..2).map(|p| p.to_ascii_lowercase());
+        match prefix.as_deref() {
+            Some("0x") => (16, &self.0[2..]),
+            Some("0o") => (8, &self.0[2..]),
+            Some("0b") => (2, &self.0[2..]),
+            _ => (10, &self.0),
+        }
+    }
+
+    /// Whether the literal is written without a fraction or an exponent
+    pub fn is_integer(&self) -> bool {
+        let (radix, digits) = self.radix();
+        radix != 10 || !digits.contains(['.', 'e', 'E'])
+    }
+
+    /// The exact value, `None` if the text isn't a valid number
+    pub fn to_decimal(&self) -> Option<Decimal> {
+        let (radix, digits) = self.radix();
+        let digits = digits.replace('_', "");
+        if digits.is_empty() {
+            return None;
+        }
+        if radix != 10 {
+            // least significant decimal digit first
+            let mut decimal = vec![0u8];
+            for c in digits.chars() {
+                let mut carry = c.to_digit(radix)?;
+                for d in decimal.iter_mut() {
+                    let v = *d as u32 * radix + carry;
+                    *d = (v % 10) as u8;
+                    carry = v / 10;
+                }
+                while carry > 0 {
+                    decimal.push((carry % 10) as u8);
+                    carry /= 10;
+                }
+            }
+            let digits = decimal.iter().rev().map(|d| char::from(b'0' + d)).collect();
+            return Some(Decimal::new(digits, 0));
+        }
+        let (mantissa, exponent) = match digits.find(['e', 'E']) {
+            Some(pos) => (&digits[..pos], digits[pos + 1..].parse::<i64>().ok()?),
+            None => (digits.as_str(), 0),
+        };
+        let (int, frac) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+        if int.is_empty() && frac.is_empty()
+            || !int.chars().chain(frac.chars()).all(|c| c.is_ascii_digit())
+        {
+            return None;
+        }
+        let scale = (frac.len() as i64).checked_sub(exponent)?;
+        Some(Decimal::new(format!("{int}{frac}"), scale))
+    }
+
+    /// The value if it is a whole number that fits in `u64`
+    pub fn as_u64(&self) -> Option<u64> {
+        let Decimal { digits, scale } = self.to_decimal()?;
+        if digits == "0" {
+            return Some(0);
+        }
+        if scale <= 0 {
+            let zeros = usize::try_from(-scale).ok()?;
+            if digits.len() + zeros > 20 {
+                return None;
+            }
+            format!("{digits}{}", "0".repeat(zeros)).parse().ok()
+        } else {
+            let split = digits.len().saturating_sub(usize::try_from(scale).ok()?);
+            let (int, frac) = digits.split_at(split);
+            if frac.chars().any(|c| c != '0') {
+                return None;
+            }
+            int.parse().ok()
+        }
+    }
+
+    /// The value if it is a whole number that fits in `i64`
+    pub fn as_i64(&self) -> Option<i64> {
+        self.as_u64().and_then(|v| i64::try_from(v).ok())
+    }
+
+    /// The nearest `f64`, which may lose precision
+    pub fn as_f64(&self) -> Option<f64> {
+        let (radix, digits) = self.radix();
+        let digits = digits.replace('_', "");
+        if radix == 10 {
+            return digits.parse().ok();
+        }
+        if digits.is_empty() {
+            return None;
+        }
+        digits.chars().try_fold(0.0, |acc, c| {
+            Some(acc * radix as f64 + c.to_digit(radix)? as f64)
+        })
+    }
 }
-impl std::fmt::Display for Literal {
+impl std::fmt::Display for Number {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// An exact decimal value of `digits * 10^-scale`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Decimal {
+    /// Decimal digits without leading zeros, `0` for zero
+    pub digits: String,
+    /// The number of digits after the decimal point, negative for trailing zeros
+    pub scale: i64,
+}
+impl Decimal {
+    fn new(digits: String, scale: i64) -> Self {
+        let digits = match digits.trim_start_matches('0') {
+            "" => "0".to_string(),
+            trimmed => trimmed.to_string(),
+        };
+        Decimal { digits, scale }
+    }
+}
+impl std::fmt::Display for Decimal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.scale <= 0 {
+            write!(f, "{}", self.digits)?;
+            if self.digits != "0" {
+                for _ in 0..-self.scale {
+                    write!(f, "0")?;
+                }
+            }
+            return Ok(());
+        }
+        let scale = self.scale as usize;
+        let digits = format!("{:0>width$}", self.digits, width = scale + 1);
+        let (int, frac) = digits.split_at(digits.len() - scale);
+        write!(f, "{int}.{frac}")
+    }
+}
+
+/// The spelling of a cast
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CastKind {
+    /// `CAST(expr AS type)`
+    Cast,
+    /// `TRY_CAST(expr AS type)`, `NULL` instead of an error if the cast fails
+    TryCast,
+    /// `SAFE_CAST(expr AS type)`, BigQuery's `TRY_CAST`
+    SafeCast,
+    /// `expr::type`
+    DoubleColon,
+}
+impl Unparse for CastKind {
+    fn unparse(&self, w: &mut SqlWriter) -> std::fmt::Result {
         match self {
-            Self::String(s) => write!(f, "'{}'", s),
-            Self::UnsignedInteger(i) => write!(f, "{}", i),
-            Self::UnsignedFloat(fl) => write!(f, "{}", fl),
+            Self::Cast => w.keyword("CAST"),
+            Self::TryCast => w.keyword("TRY_CAST"),
+            Self::SafeCast => w.keyword("SAFE_CAST"),
+            Self::DoubleColon => w.keyword("::"),
         }
     }
 }
 
 /// Unary operators
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum UnaryOp {
     Plus,
     Minus,
+    // ~
+    BitwiseNot,
+    Not,
 }
 
-impl std::fmt::Display for UnaryOp {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl Unparse for UnaryOp {
+    fn unparse(&self, w: &mut SqlWriter) -> std::fmt::Result {
+        match self {
+            Self::Plus => w.keyword("+"),
+            Self::Minus => w.keyword("-"),
+            Self::BitwiseNot => w.keyword("~"),
+            Self::Not => w.keyword("NOT"),
+        }
+    }
+}
+
+/// Whether a function like `first_value` skips the `NULL` values
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum NullTreatment {
+    IgnoreNulls,
+    RespectNulls,
+}
+impl Unparse for NullTreatment {
+    fn unparse(&self, w: &mut SqlWriter) -> std::fmt::Result {
         match self {
-            Self::Plus => write!(f, "+"),
-            Self::Minus => write!(f, "-"),
+            Self::IgnoreNulls => w.keyword("IGNORE NULLS"),
+            Self::RespectNulls => w.keyword("RESPECT NULLS"),
         }
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "value"))]
 pub enum FunctionArg {
-    Wildcard,
+    Wildcard { span: NodeSpan },
     Expr(Expr),
 }
-impl std::fmt::Display for FunctionArg {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl Spanned for FunctionArg {
+    fn span(&self) -> Span {
         match self {
-            Self::Wildcard => write!(f, "*"),
-            Self::Expr(expr) => write!(f, "{}", expr),
+            Self::Wildcard { span } => **span,
+            Self::Expr(expr) => expr.span(),
+        }
+    }
+}
+impl Unparse for FunctionArg {
+    fn unparse(&self, w: &mut SqlWriter) -> std::fmt::Result {
+        match self {
+            Self::Wildcard { .. } => w.write_str("*"),
+            Self::Expr(expr) => expr.unparse(w),
         }
     }
 }
 
-#[derive(Debug, Clone)]
+/// A `WHEN condition THEN result` arm of a `CASE` expression
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CaseWhen {
+    pub condition: Expr,
+    pub result: Expr,
+    pub span: NodeSpan,
+}
+impl Spanned for CaseWhen {
+    fn span(&self) -> Span {
+        *self.span
+    }
+}
+impl Unparse for CaseWhen {
+    fn unparse(&self, w: &mut SqlWriter) -> std::fmt::Result {
+        w.keyword("WHEN ")?;
+        self.condition.unparse(w)?;
+        w.keyword(" THEN ")?;
+        self.result.unparse(w)
+    }
+}
+
+/// The right side of a pattern matching predicate like `LIKE`
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "value"))]
+pub enum Pattern {
+    Expr(Box<Expr>),
+    /// `ANY (p1, p2, ...)`, matches if any of the patterns matches
+    Any(Vec<Expr>),
+    /// `ALL (p1, p2, ...)`, matches if all of the patterns match
+    All(Vec<Expr>),
+}
+impl Unparse for Pattern {
+    fn unparse(&self, w: &mut SqlWriter) -> std::fmt::Result {
+        let (quantifier, patterns) = match self {
+            Self::Expr(expr) => return expr.unparse(w),
+            Self::Any(patterns) => ("ANY (", patterns),
+            Self::All(patterns) => ("ALL (", patterns),
+        };
+        w.keyword(quantifier)?;
+        w.comma_separated(patterns)?;
+        w.write_str(")")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "value"))]
 pub enum Window {
     WindowRef(Ident),
     WindowSpec(WindowSpec),
 }
-impl std::fmt::Display for Window {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl Spanned for Window {
+    fn span(&self) -> Span {
         match self {
-            Self::WindowRef(name) => write!(f, "{}", name),
-            Self::WindowSpec(spec) => write!(f, "({})", spec),
+            Self::WindowRef(name) => *name.span,
+            Self::WindowSpec(spec) => *spec.span,
+        }
+    }
+}
+impl Unparse for Window {
+    fn unparse(&self, w: &mut SqlWriter) -> std::fmt::Result {
+        match self {
+            Self::WindowRef(name) => write!(w, "{}", name),
+            Self::WindowSpec(spec) => {
+                w.write_str("(")?;
+                spec.unparse(w)?;
+                w.write_str(")")
+            }
         }
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct WindowSpec {
+    /// An existing window the spec inherits from, like `w` in `OVER (w ORDER BY a)`
+    pub window_name: Option<Ident>,
     pub partition_by: Vec<Expr>,
     pub order_by: Vec<OrderByExpr>,
+    pub frame: Option<WindowFrame>,
+    pub span: NodeSpan,
+}
+impl Spanned for WindowSpec {
+    fn span(&self) -> Span {
+        *self.span
+    }
 }
 
-impl std::fmt::Display for WindowSpec {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl Unparse for WindowSpec {
+    fn unparse(&self, w: &mut SqlWriter) -> std::fmt::Result {
         let mut delim = "";
+        if let Some(window_name) = &self.window_name {
+            delim = " ";
+            write!(w, "{}", window_name)?;
+        }
         if !self.partition_by.is_empty() {
+            w.write_str(delim)?;
             delim = " ";
-            write!(
-                f,
-                "PARTITION BY {}",
-                self.partition_by
-                    .iter()
-                    .map(|e| e.to_string())
-                    .collect::<Vec<String>>()
-                    .join(", ")
-            )?;
+            w.keyword("PARTITION BY ")?;
+            w.comma_separated(&self.partition_by)?;
         }
         if !self.order_by.is_empty() {
-            f.write_str(delim)?;
-            // delim = " ";
-            write!(
-                f,
-                "ORDER BY {}",
-                self.order_by
-                    .iter()
-                    .map(|e| e.to_string())
-                    .collect::<Vec<String>>()
-                    .join(", ")
-            )?;
+            w.write_str(delim)?;
+            delim = " ";
+            w.keyword("ORDER BY ")?;
+            w.comma_separated(&self.order_by)?;
+        }
+        if let Some(frame) = &self.frame {
+            w.write_str(delim)?;
+            frame.unparse(w)?;
         }
         Ok(())
     }
 }
+
+/// `units start` or `units BETWEEN start AND end`, followed by an optional `EXCLUDE`
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WindowFrame {
+    pub units: WindowFrameUnits,
+    pub start: WindowFrameBound,
+    /// Without `BETWEEN` the frame ends at the current row
+    pub end: Option<WindowFrameBound>,
+    pub exclude: Option<WindowFrameExclude>,
+    pub span: NodeSpan,
+}
+impl Spanned for WindowFrame {
+    fn span(&self) -> Span {
+        *self.span
+    }
+}
+impl Unparse for WindowFrame {
+    fn unparse(&self, w: &mut SqlWriter) -> std::fmt::Result {
+        self.units.unparse(w)?;
+        match &self.end {
+            Some(end) => {
+                w.keyword(" BETWEEN ")?;
+                self.start.unparse(w)?;
+                w.keyword(" AND ")?;
+                end.unparse(w)?;
+            }
+            None => {
+                w.write_str(" ")?;
+                self.start.unparse(w)?;
+            }
+        }
+        if let Some(exclude) = &self.exclude {
+            w.keyword(" EXCLUDE ")?;
+            exclude.unparse(w)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum WindowFrameUnits {
+    Rows,
+    Range,
+    Groups,
+}
+impl Unparse for WindowFrameUnits {
+    fn unparse(&self, w: &mut SqlWriter) -> std::fmt::Result {
+        match self {
+            Self::Rows => w.keyword("ROWS"),
+            Self::Range => w.keyword("RANGE"),
+            Self::Groups => w.keyword("GROUPS"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "value"))]
+pub enum WindowFrameBound {
+    UnboundedPreceding,
+    /// `offset PRECEDING`, the offset is a number of rows or groups, or a
+    /// distance of the ordering value like an interval for `RANGE`
+    Preceding(Box<Expr>),
+    CurrentRow,
+    /// `offset FOLLOWING`
+    Following(Box<Expr>),
+    UnboundedFollowing,
+}
+impl Unparse for WindowFrameBound {
+    fn unparse(&self, w: &mut SqlWriter) -> std::fmt::Result {
+        match self {
+            Self::UnboundedPreceding => w.keyword("UNBOUNDED PRECEDING"),
+            Self::Preceding(offset) => {
+                offset.unparse(w)?;
+                w.keyword(" PRECEDING")
+            }
+            Self::CurrentRow => w.keyword("CURRENT ROW"),
+            Self::Following(offset) => {
+                offset.unparse(w)?;
+                w.keyword(" FOLLOWING")
+            }
+            Self::UnboundedFollowing => w.keyword("UNBOUNDED FOLLOWING"),
+        }
+    }
+}
+
+/// Rows around the current one left out of the frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum WindowFrameExclude {
+    CurrentRow,
+    /// The current row and its peers
+    Group,
+    /// The peers of the current row but not itself
+    Ties,
+    NoOthers,
+}
+impl Unparse for WindowFrameExclude {
+    fn unparse(&self, w: &mut SqlWriter) -> std::fmt::Result {
+        match self {
+            Self::CurrentRow => w.keyword("CURRENT ROW"),
+            Self::Group => w.keyword("GROUP"),
+            Self::Ties => w.keyword("TIES"),
+            Self::NoOthers => w.keyword("NO OTHERS"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum IntervalUnit {
+    Year,
+    Month,
+    Week,
+    Day,
+    Hour,
+    Minute,
+    Second,
+}
+impl Unparse for IntervalUnit {
+    fn unparse(&self, w: &mut SqlWriter) -> std::fmt::Result {
+        match self {
+            Self::Year => w.keyword("YEAR"),
+            Self::Month => w.keyword("MONTH"),
+            Self::Week => w.keyword("WEEK"),
+            Self::Day => w.keyword("DAY"),
+            Self::Hour => w.keyword("HOUR"),
+            Self::Minute => w.keyword("MINUTE"),
+            Self::Second => w.keyword("SECOND"),
+        }
+    }
+}
+
+display_unparsed!(
+    Expr,
+    BinaryOp,
+    Literal,
+    CastKind,
+    UnaryOp,
+    NullTreatment,
+    FunctionArg,
+    CaseWhen,
+    Pattern,
+    Window,
+    WindowSpec,
+    WindowFrame,
+    WindowFrameUnits,
+    WindowFrameBound,
+    WindowFrameExclude,
+    IntervalUnit,
+);
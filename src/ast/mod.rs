@@ -1,15 +1,126 @@
+pub mod data_type;
 pub mod expr;
+pub(crate) mod precedence;
 pub mod set_expr;
 pub mod statement;
 pub mod table_ref;
+pub(crate) mod unparse;
+pub mod visitor;
+
+use std::fmt::Write;
+
+/// A byte range of the source sql, left closed and right open.
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Span { start, end }
+    }
+
+    pub fn range(&self) -> std::ops::Range<usize> {
+        self.start..self.end
+    }
+}
+impl std::fmt::Debug for Span {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}..{}", self.start, self.end)
+    }
+}
+impl From<std::ops::Range<usize>> for Span {
+    fn from(range: std::ops::Range<usize>) -> Self {
+        Span::new(range.start, range.end)
+    }
+}
+
+/// The [`Span`] of an AST node.
+///
+/// Node spans all compare equal and don't take part in hashing, so two trees
+/// parsed from differently formatted sql are equal.
+#[derive(Clone, Copy, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct NodeSpan(pub Span);
+impl std::ops::Deref for NodeSpan {
+    type Target = Span;
+
+    fn deref(&self) -> &Span {
+        &self.0
+    }
+}
+impl PartialEq for NodeSpan {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+impl Eq for NodeSpan {}
+impl PartialOrd for NodeSpan {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for NodeSpan {
+    fn cmp(&self, _: &Self) -> std::cmp::Ordering {
+        std::cmp::Ordering::Equal
+    }
+}
+impl std::hash::Hash for NodeSpan {
+    fn hash<H: std::hash::Hasher>(&self, _: &mut H) {}
+}
+impl std::fmt::Debug for NodeSpan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+impl From<Span> for NodeSpan {
+    fn from(span: Span) -> Self {
+        NodeSpan(span)
+    }
+}
+impl From<std::ops::Range<usize>> for NodeSpan {
+    fn from(range: std::ops::Range<usize>) -> Self {
+        NodeSpan(range.into())
+    }
+}
+
+/// A node that knows where it comes from in the source sql
+pub trait Spanned {
+    fn span(&self) -> Span;
+}
 
 /// An identifier
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Ident {
     pub value: String,
+    pub span: NodeSpan,
+}
+impl Ident {
+    pub fn new(value: impl Into<String>) -> Self {
+        Ident {
+            value: value.into(),
+            span: NodeSpan::default(),
+        }
+    }
 }
 impl std::fmt::Display for Ident {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{0}", self.value)
     }
 }
+impl unparse::Unparse for Ident {
+    fn unparse(&self, w: &mut unparse::SqlWriter) -> std::fmt::Result {
+        w.write_str(&self.value)
+    }
+}
+impl Spanned for Ident {
+    fn span(&self) -> Span {
+        *self.span
+    }
+}
@@ -0,0 +1,85 @@
+//! Binding powers of the expression operators, a higher one binds tighter. The
+//! parser groups operands by them and `Display` parenthesizes operands with them.
+//!
+//! From loosest to tightest, like PostgreSQL:
+//!
+//! ```text
+//!  10  OR
+//!  20  AND
+//!  30  NOT
+//!  40  IS
+//!  50  = != < > <= >=
+//!  60  IN BETWEEN LIKE ILIKE SIMILAR TO REGEXP RLIKE
+//!  70  || | & << >>
+//!  80  + -
+//!  90  * / // %
+//! 100  ^ **
+//! 110  unary + - ~
+//! 120  ::
+//! ```
+//!
+//! `||`, `|`, `&` and the shifts are on the single level PostgreSQL gives its
+//! other operators, so `a | b & c` is `(a | b) & c` unlike in MySQL. `^` binds
+//! tighter than `*` in both.
+
+use super::expr::{BinaryOp, CastKind, Expr, UnaryOp};
+
+pub(crate) const IS: u32 = 40;
+/// `IN`, `BETWEEN` and the pattern matching operators
+pub(crate) const PREDICATE: u32 = 60;
+pub(crate) const DOUBLE_COLON: u32 = 120;
+
+impl UnaryOp {
+    pub(crate) fn binding_power(self) -> u32 {
+        match self {
+            Self::Not => 30,
+            Self::Plus | Self::Minus | Self::BitwiseNot => 110,
+        }
+    }
+}
+
+impl BinaryOp {
+    pub(crate) fn binding_power(self) -> u32 {
+        match self {
+            Self::Or => 10,
+            Self::And => 20,
+            Self::Gt | Self::Lt | Self::GtEq | Self::LtEq | Self::Eq | Self::NotEq => 50,
+            Self::StringConcat
+            | Self::BitwiseOr
+            | Self::BitwiseAnd
+            | Self::ShiftLeft
+            | Self::ShiftRight => 70,
+            Self::Add | Self::Sub => 80,
+            Self::Mul | Self::Div | Self::IntDiv | Self::Mod => 90,
+            Self::Exponent | Self::Caret => 100,
+        }
+    }
+}
+
+impl Expr {
+    /// Binding power of the operator at the root of the expr, the other exprs
+    /// bind tighter than any operator.
+    pub(crate) fn binding_power(&self) -> u32 {
+        match self {
+            Self::UnaryOp { op, .. } => op.binding_power(),
+            Self::BinaryOp { op, .. } => op.binding_power(),
+            Self::IsNull { .. }
+            | Self::IsTrue { .. }
+            | Self::IsFalse { .. }
+            | Self::IsUnknown { .. }
+            | Self::IsDistinctFrom { .. } => IS,
+            Self::InSubquery { .. }
+            | Self::InList { .. }
+            | Self::Between { .. }
+            | Self::Like { .. }
+            | Self::ILike { .. }
+            | Self::SimilarTo { .. }
+            | Self::RLike { .. } => PREDICATE,
+            Self::Cast {
+                kind: CastKind::DoubleColon,
+                ..
+            } => DOUBLE_COLON,
+            _ => u32::MAX,
+        }
+    }
+}
@@ -1,8 +1,16 @@
-use super::{expr::Expr, Ident};
+use std::fmt::Write;
+
+use super::unparse::{display_unparsed, SqlWriter, Unparse};
+use super::{expr::Expr, Ident, NodeSpan, Span, Spanned};
 use crate::ast::expr::WindowSpec;
+use crate::ast::statement::SelectStatement;
 use crate::ast::table_ref::TableRef;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type"))]
+// most set exprs are a single select, it isn't boxed to save an allocation
+#[allow(clippy::large_enum_variant)]
 pub enum SetExpr {
     Select {
         distinct: bool,
@@ -12,10 +20,48 @@ pub enum SetExpr {
         group_by: Vec<Expr>,
         having: Option<Expr>,
         named_windows: Vec<NamedWindowDef>,
+        span: NodeSpan,
+    },
+    /// `left UNION | INTERSECT | EXCEPT [ALL | DISTINCT] right`
+    SetOperation {
+        op: SetOperator,
+        quantifier: Option<SetQuantifier>,
+        left: Box<SetExpr>,
+        right: Box<SetExpr>,
+        span: NodeSpan,
     },
+    /// `VALUES (expr, ...), ...`
+    Values {
+        rows: Vec<Vec<Expr>>,
+        span: NodeSpan,
+    },
+    /// A parenthesized operand of a set operation that has its own `WITH`,
+    /// `ORDER BY`, `LIMIT` or `OFFSET`, the parentheses of other operands are
+    /// only kept in the tree shape.
+    Query(Box<SelectStatement>),
+}
+impl SetExpr {
+    /// Set operations are left associative and `INTERSECT` binds tighter than
+    /// `UNION` and `EXCEPT`, the other set exprs bind tighter than any of them.
+    pub(crate) fn binding_power(&self) -> u32 {
+        match self {
+            Self::SetOperation { op, .. } => op.binding_power(),
+            _ => u32::MAX,
+        }
+    }
 }
-impl std::fmt::Display for SetExpr {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl Spanned for SetExpr {
+    fn span(&self) -> Span {
+        match self {
+            Self::Select { span, .. }
+            | Self::Values { span, .. }
+            | Self::SetOperation { span, .. } => **span,
+            Self::Query(query) => *query.span,
+        }
+    }
+}
+impl Unparse for SetExpr {
+    fn unparse(&self, w: &mut SqlWriter) -> std::fmt::Result {
         match self {
             Self::Select {
                 distinct,
@@ -25,117 +71,217 @@ impl std::fmt::Display for SetExpr {
                 group_by,
                 having,
                 named_windows,
+                ..
             } => {
-                write!(
-                    f,
-                    "SELECT {}{}",
-                    if *distinct { "DISTINCT " } else { "" },
-                    projection
-                        .iter()
-                        .map(|p| p.to_string())
-                        .collect::<Vec<_>>()
-                        .join(", "),
-                )?;
+                w.keyword("SELECT ")?;
+                if *distinct {
+                    w.keyword("DISTINCT ")?;
+                }
+                w.comma_separated(projection)?;
                 if let Some(from) = from {
-                    write!(f, " FROM {}", from)?;
+                    w.keyword(" FROM ")?;
+                    from.unparse(w)?;
                 }
                 if let Some(selection) = selection {
-                    write!(f, " WHERE {}", selection)?;
+                    w.keyword(" WHERE ")?;
+                    selection.unparse(w)?;
                 }
                 if !group_by.is_empty() {
-                    write!(
-                        f,
-                        " GROUP BY {}",
-                        group_by
-                            .iter()
-                            .map(|p| p.to_string())
-                            .collect::<Vec<_>>()
-                            .join(", "),
-                    )?;
+                    w.keyword(" GROUP BY ")?;
+                    w.comma_separated(group_by)?;
                 }
                 if let Some(expr) = having {
-                    write!(f, " Having {}", expr)?;
+                    w.keyword(" HAVING ")?;
+                    expr.unparse(w)?;
                 }
                 if !named_windows.is_empty() {
-                    write!(
-                        f,
-                        " WINDOW {}",
-                        named_windows
-                            .iter()
-                            .map(|w| w.to_string())
-                            .collect::<Vec<String>>()
-                            .join(", ")
-                    )?;
+                    w.keyword(" WINDOW ")?;
+                    w.comma_separated(named_windows)?;
+                }
+                Ok(())
+            }
+            Self::Values { rows, .. } => {
+                w.keyword("VALUES ")?;
+                for (idx, row) in rows.iter().enumerate() {
+                    if idx > 0 {
+                        w.write_str(", ")?;
+                    }
+                    w.write_str("(")?;
+                    w.comma_separated(row)?;
+                    w.write_str(")")?;
                 }
                 Ok(())
             }
+            Self::SetOperation {
+                op,
+                quantifier,
+                left,
+                right,
+                ..
+            } => {
+                if left.binding_power() < self.binding_power() {
+                    w.write_str("(")?;
+                    left.unparse(w)?;
+                    w.write_str(")")?;
+                } else {
+                    left.unparse(w)?;
+                }
+                w.write_str(" ")?;
+                op.unparse(w)?;
+                w.write_str(" ")?;
+                if let Some(quantifier) = quantifier {
+                    quantifier.unparse(w)?;
+                    w.write_str(" ")?;
+                }
+                if right.binding_power() <= self.binding_power() {
+                    w.write_str("(")?;
+                    right.unparse(w)?;
+                    w.write_str(")")
+                } else {
+                    right.unparse(w)
+                }
+            }
+            Self::Query(query) => {
+                w.write_str("(")?;
+                query.unparse(w)?;
+                w.write_str(")")
+            }
         }
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SetOperator {
+    Union,
+    Intersect,
+    Except,
+}
+impl SetOperator {
+    pub(crate) fn binding_power(&self) -> u32 {
+        match self {
+            Self::Union | Self::Except => 1,
+            Self::Intersect => 2,
+        }
+    }
+}
+impl Unparse for SetOperator {
+    fn unparse(&self, w: &mut SqlWriter) -> std::fmt::Result {
+        match self {
+            Self::Union => w.keyword("UNION"),
+            Self::Intersect => w.keyword("INTERSECT"),
+            Self::Except => w.keyword("EXCEPT"),
+        }
+    }
+}
+
+/// `DISTINCT` is the default of a set operation when neither is written
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SetQuantifier {
+    All,
+    Distinct,
+}
+impl Unparse for SetQuantifier {
+    fn unparse(&self, w: &mut SqlWriter) -> std::fmt::Result {
+        match self {
+            Self::All => w.keyword("ALL"),
+            Self::Distinct => w.keyword("DISTINCT"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "value"))]
 pub enum SelectItem {
     UnnamedExpr(Expr),
-    ExprWithAlias { expr: Expr, alias: Ident },
+    ExprWithAlias {
+        expr: Expr,
+        alias: Ident,
+        span: NodeSpan,
+    },
     Wildcard(WildcardOptions),
 }
-impl std::fmt::Display for SelectItem {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl Spanned for SelectItem {
+    fn span(&self) -> Span {
+        match self {
+            Self::UnnamedExpr(expr) => expr.span(),
+            Self::ExprWithAlias { span, .. } => **span,
+            Self::Wildcard(options) => *options.span,
+        }
+    }
+}
+impl Unparse for SelectItem {
+    fn unparse(&self, w: &mut SqlWriter) -> std::fmt::Result {
         match self {
-            Self::UnnamedExpr(expr) => write!(f, "{}", expr),
-            Self::ExprWithAlias { expr, alias } => write!(f, "{} AS {}", expr, alias),
-            Self::Wildcard(options) => write!(f, "*{}", options),
+            Self::UnnamedExpr(expr) => expr.unparse(w),
+            Self::ExprWithAlias { expr, alias, .. } => {
+                expr.unparse(w)?;
+                w.keyword(" AS ")?;
+                alias.unparse(w)
+            }
+            Self::Wildcard(options) => {
+                w.write_str("*")?;
+                options.unparse(w)
+            }
         }
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct WildcardOptions {
     pub exclude: Vec<Ident>,
     pub except: Vec<Ident>,
+    // Including the `*`
+    pub span: NodeSpan,
 }
-impl std::fmt::Display for WildcardOptions {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        if !self.exclude.is_empty() {
-            write!(
-                f,
-                " EXCLUDE {}",
-                if self.exclude.len() == 1 {
-                    self.exclude.get(0).unwrap().to_string()
-                } else {
-                    format!(
-                        "({})",
-                        self.exclude
-                            .iter()
-                            .map(|col| col.to_string())
-                            .collect::<Vec<String>>()
-                            .join(", ")
-                    )
-                }
-            )?;
+impl Unparse for WildcardOptions {
+    fn unparse(&self, w: &mut SqlWriter) -> std::fmt::Result {
+        if let [column] = self.exclude.as_slice() {
+            w.keyword(" EXCLUDE ")?;
+            column.unparse(w)?;
+        } else if !self.exclude.is_empty() {
+            w.keyword(" EXCLUDE (")?;
+            w.comma_separated(&self.exclude)?;
+            w.write_str(")")?;
         }
         if !self.except.is_empty() {
-            write!(
-                f,
-                " EXCEPT ({})",
-                self.except
-                    .iter()
-                    .map(|col| col.to_string())
-                    .collect::<Vec<String>>()
-                    .join(", ")
-            )?;
+            w.keyword(" EXCEPT (")?;
+            w.comma_separated(&self.except)?;
+            w.write_str(")")?;
         }
         Ok(())
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NamedWindowDef {
     pub name: Ident,
     pub spec: WindowSpec,
+    pub span: NodeSpan,
 }
-impl std::fmt::Display for NamedWindowDef {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{} AS ({})", self.name, self.spec)
+impl Spanned for NamedWindowDef {
+    fn span(&self) -> Span {
+        *self.span
     }
 }
+impl Unparse for NamedWindowDef {
+    fn unparse(&self, w: &mut SqlWriter) -> std::fmt::Result {
+        self.name.unparse(w)?;
+        w.keyword(" AS (")?;
+        self.spec.unparse(w)?;
+        w.write_str(")")
+    }
+}
+
+display_unparsed!(
+    SetExpr,
+    SetOperator,
+    SetQuantifier,
+    SelectItem,
+    WildcardOptions,
+    NamedWindowDef,
+);
@@ -1,110 +1,183 @@
+use std::fmt::Write;
+
+use super::unparse::{display_unparsed, SqlWriter, Unparse};
 use super::{expr::Expr, set_expr::SetExpr};
-use crate::ast::Ident;
+use crate::ast::{Ident, NodeSpan, Span, Spanned};
+
+/// A top-level sql statement
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "value"))]
+pub enum Statement {
+    Select(Box<SelectStatement>),
+    /// Placeholder of the skipped tokens of a statement that failed to parse,
+    /// only produced by a recovering parse
+    Error {
+        span: NodeSpan,
+    },
+}
+impl Spanned for Statement {
+    fn span(&self) -> Span {
+        match self {
+            Self::Select(stmt) => *stmt.span,
+            Self::Error { span } => **span,
+        }
+    }
+}
+impl Unparse for Statement {
+    fn unparse(&self, w: &mut SqlWriter) -> std::fmt::Result {
+        match self {
+            Self::Select(stmt) => stmt.unparse(w),
+            Self::Error { .. } => w.write_str("<error>"),
+        }
+    }
+}
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SelectStatement {
     pub with: Option<With>,
     pub body: SetExpr,
     pub order_by: Vec<OrderByExpr>,
     pub limit: Option<Expr>,
     pub offset: Option<Expr>,
+    pub span: NodeSpan,
 }
-impl std::fmt::Display for SelectStatement {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl Spanned for SelectStatement {
+    fn span(&self) -> Span {
+        *self.span
+    }
+}
+impl Unparse for SelectStatement {
+    fn unparse(&self, w: &mut SqlWriter) -> std::fmt::Result {
         if let Some(with) = &self.with {
-            write!(f, "{} ", with)?;
+            with.unparse(w)?;
+            w.write_str(" ")?;
         }
-        write!(f, "{}", self.body)?;
-        if self.order_by.len() > 0 {
-            write!(
-                f,
-                " ORDER BY {}",
-                self.order_by
-                    .iter()
-                    .map(|o| o.to_string())
-                    .collect::<Vec<_>>()
-                    .join(", ")
-            )?;
+        self.body.unparse(w)?;
+        if !self.order_by.is_empty() {
+            w.keyword(" ORDER BY ")?;
+            w.comma_separated(&self.order_by)?;
         }
         if let Some(limit) = &self.limit {
-            write!(f, " LIMIT {}", limit)?;
+            w.keyword(" LIMIT ")?;
+            limit.unparse(w)?;
         }
         if let Some(offset) = &self.offset {
-            write!(f, " OFFSET {}", offset)?;
+            w.keyword(" OFFSET ")?;
+            offset.unparse(w)?;
         }
         Ok(())
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct OrderByExpr {
     pub expr: Expr,
     pub asc: Option<bool>,
+    /// `NULLS FIRST` or `NULLS LAST`, the default depends on the direction and the dialect
+    pub nulls_first: Option<bool>,
+    pub span: NodeSpan,
+}
+impl Spanned for OrderByExpr {
+    fn span(&self) -> Span {
+        *self.span
+    }
 }
-impl std::fmt::Display for OrderByExpr {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.expr)?;
+impl Unparse for OrderByExpr {
+    fn unparse(&self, w: &mut SqlWriter) -> std::fmt::Result {
+        self.expr.unparse(w)?;
         if let Some(asc) = &self.asc {
             if *asc {
-                write!(f, " ASC")?;
+                w.keyword(" ASC")?;
+            } else {
+                w.keyword(" DESC")?;
+            }
+        }
+        if let Some(nulls_first) = &self.nulls_first {
+            if *nulls_first {
+                w.keyword(" NULLS FIRST")?;
             } else {
-                write!(f, " DESC")?;
+                w.keyword(" NULLS LAST")?;
             }
         }
         Ok(())
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct With {
     pub recursive: bool,
     pub cte_tables: Vec<Cte>,
+    pub span: NodeSpan,
+}
+impl Spanned for With {
+    fn span(&self) -> Span {
+        *self.span
+    }
 }
-impl std::fmt::Display for With {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "WITH {}{}",
-            if self.recursive { "RECURSIVE " } else { "" },
-            self.cte_tables
-                .iter()
-                .map(|cte| cte.to_string())
-                .collect::<Vec<String>>()
-                .join(", ")
-        )
+impl Unparse for With {
+    fn unparse(&self, w: &mut SqlWriter) -> std::fmt::Result {
+        w.keyword("WITH ")?;
+        if self.recursive {
+            w.keyword("RECURSIVE ")?;
+        }
+        w.comma_separated(&self.cte_tables)
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Cte {
     pub alias: TableAlias,
     pub query: Box<SelectStatement>,
+    pub span: NodeSpan,
+}
+impl Spanned for Cte {
+    fn span(&self) -> Span {
+        *self.span
+    }
 }
-impl std::fmt::Display for Cte {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{} AS ({})", self.alias, self.query)
+impl Unparse for Cte {
+    fn unparse(&self, w: &mut SqlWriter) -> std::fmt::Result {
+        self.alias.unparse(w)?;
+        w.keyword(" AS (")?;
+        self.query.unparse(w)?;
+        w.write_str(")")
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TableAlias {
     pub name: Ident,
     pub columns: Vec<Ident>,
+    pub span: NodeSpan,
 }
-impl std::fmt::Display for TableAlias {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.name)?;
+impl Spanned for TableAlias {
+    fn span(&self) -> Span {
+        *self.span
+    }
+}
+impl Unparse for TableAlias {
+    fn unparse(&self, w: &mut SqlWriter) -> std::fmt::Result {
+        self.name.unparse(w)?;
         if !self.columns.is_empty() {
-            write!(
-                f,
-                " ({})",
-                self.columns
-                    .iter()
-                    .map(|col| col.to_string())
-                    .collect::<Vec<String>>()
-                    .join(", ")
-            )?;
+            w.write_str(" (")?;
+            w.comma_separated(&self.columns)?;
+            w.write_str(")")?;
         }
         Ok(())
     }
 }
+
+display_unparsed!(
+    Statement,
+    SelectStatement,
+    OrderByExpr,
+    With,
+    Cte,
+    TableAlias,
+);
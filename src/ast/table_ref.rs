@@ -1,38 +1,61 @@
+use std::fmt::Write;
+
 use crate::ast::expr::Expr;
-use crate::ast::statement::SelectStatement;
-use crate::ast::Ident;
+use crate::ast::statement::{SelectStatement, TableAlias};
+use crate::ast::unparse::{display_unparsed, SqlWriter, Unparse};
+use crate::ast::{Ident, NodeSpan, Span, Spanned};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type"))]
 pub enum TableRef {
     BaseTable {
         name: TableName,
-        alias: Option<Ident>,
+        alias: Option<TableAlias>,
+        span: NodeSpan,
     },
     Subquery {
         subquery: Box<SelectStatement>,
-        alias: Option<Ident>,
+        alias: Option<TableAlias>,
+        span: NodeSpan,
     },
     Join {
         op: JoinOp,
         condition: JoinCondition,
         left: Box<TableRef>,
         right: Box<TableRef>,
+        span: NodeSpan,
     },
 }
-impl std::fmt::Display for TableRef {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl Spanned for TableRef {
+    fn span(&self) -> Span {
+        match self {
+            Self::BaseTable { span, .. }
+            | Self::Subquery { span, .. }
+            | Self::Join { span, .. } => **span,
+        }
+    }
+}
+impl Unparse for TableRef {
+    fn unparse(&self, w: &mut SqlWriter) -> std::fmt::Result {
         match self {
-            TableRef::BaseTable { name, alias } => {
-                write!(f, "{name}")?;
+            TableRef::BaseTable { name, alias, .. } => {
+                name.unparse(w)?;
                 if let Some(alias) = alias {
-                    write!(f, " AS {alias}")?;
+                    w.keyword(" AS ")?;
+                    alias.unparse(w)?;
                 }
                 Ok(())
             }
-            TableRef::Subquery { subquery, alias } => {
-                write!(f, "({subquery})")?;
+            TableRef::Subquery {
+                subquery, alias, ..
+            } => {
+                w.write_str("(")?;
+                subquery.unparse(w)?;
+                w.write_str(")")?;
                 if let Some(alias) = alias {
-                    write!(f, " AS {alias}")?;
+                    w.keyword(" AS ")?;
+                    alias.unparse(w)?;
                 }
                 Ok(())
             }
@@ -41,35 +64,56 @@ impl std::fmt::Display for TableRef {
                 condition,
                 left,
                 right,
+                ..
             } => {
-                write!(f, "({left} {op} {right}")?;
+                // joins are left associative, a join on the right side needs parentheses
+                left.unparse(w)?;
+                w.write_str(" ")?;
+                op.unparse(w)?;
+                w.write_str(" ")?;
+                if let TableRef::Join { .. } = **right {
+                    w.write_str("(")?;
+                    right.unparse(w)?;
+                    w.write_str(")")?;
+                } else {
+                    right.unparse(w)?;
+                }
                 match condition {
-                    JoinCondition::On(expr) => write!(f, " ON {}", expr)?,
+                    JoinCondition::On(expr) => {
+                        w.keyword(" ON ")?;
+                        expr.unparse(w)?;
+                    }
                     JoinCondition::None => {}
                 }
-                write!(f, ")")?;
                 Ok(())
             }
         }
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TableName {
     pub database: Option<Ident>,
     pub table: Ident,
+    pub span: NodeSpan,
 }
-impl std::fmt::Display for TableName {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl Spanned for TableName {
+    fn span(&self) -> Span {
+        *self.span
+    }
+}
+impl Unparse for TableName {
+    fn unparse(&self, w: &mut SqlWriter) -> std::fmt::Result {
         if let Some(database) = self.database.as_ref() {
-            write!(f, "{database}.")?;
+            write!(w, "{database}.")?;
         }
-        write!(f, "{}", self.table)?;
-        Ok(())
+        write!(w, "{}", self.table)
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum JoinOp {
     Inner,
     LeftOuter,
@@ -77,19 +121,23 @@ pub enum JoinOp {
     FullOuter,
     CrossJoin,
 }
-impl std::fmt::Display for JoinOp {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl Unparse for JoinOp {
+    fn unparse(&self, w: &mut SqlWriter) -> std::fmt::Result {
         match self {
-            JoinOp::Inner => write!(f, "INNER JOIN"),
-            JoinOp::LeftOuter => write!(f, "LEFT OUTER JOIN"),
-            JoinOp::RightOuter => write!(f, "RIGHT OUTER JOIN"),
-            JoinOp::FullOuter => write!(f, "FULL OUTER JOIN"),
-            JoinOp::CrossJoin => write!(f, "CROSS JOIN"),
+            JoinOp::Inner => w.keyword("INNER JOIN"),
+            JoinOp::LeftOuter => w.keyword("LEFT OUTER JOIN"),
+            JoinOp::RightOuter => w.keyword("RIGHT OUTER JOIN"),
+            JoinOp::FullOuter => w.keyword("FULL OUTER JOIN"),
+            JoinOp::CrossJoin => w.keyword("CROSS JOIN"),
         }
     }
 }
 
-#[derive(Debug, Clone)]
+display_unparsed!(TableRef, TableName, JoinOp);
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "value"))]
 pub enum JoinCondition {
     On(Box<Expr>),
     None,
@@ -0,0 +1,93 @@
+//! Writing AST nodes back to sql.
+//!
+//! Nodes write their keywords upper case through [`SqlWriter::keyword`], so a
+//! writer with a keyword spelling can case them as they are written, without
+//! telling them apart from identifiers afterwards. `Display` writes the nodes
+//! with upper case keywords.
+
+use std::fmt::{self, Write};
+
+pub(crate) trait Unparse {
+    fn unparse(&self, w: &mut SqlWriter) -> fmt::Result;
+}
+
+pub(crate) struct SqlWriter<'a> {
+    out: &'a mut dyn Write,
+    // Spelling of an upper case keyword
+    spell: Option<&'a dyn Fn(&str) -> String>,
+}
+impl<'a> SqlWriter<'a> {
+    pub(crate) fn new(out: &'a mut dyn Write) -> Self {
+        SqlWriter { out, spell: None }
+    }
+
+    pub(crate) fn with_spelling(out: &'a mut dyn Write, spell: &'a dyn Fn(&str) -> String) -> Self {
+        SqlWriter {
+            out,
+            spell: Some(spell),
+        }
+    }
+
+    /// Writes sql made of upper case keywords and punctuation, like ` IS NOT `.
+    pub(crate) fn keyword(&mut self, sql: &str) -> fmt::Result {
+        let Some(spell) = self.spell else {
+            return self.out.write_str(sql);
+        };
+        let mut rest = sql;
+        while !rest.is_empty() {
+            let is_word = rest.starts_with(is_word_char);
+            let end = rest
+                .find(|c: char| is_word_char(c) != is_word)
+                .unwrap_or(rest.len());
+            if is_word {
+                self.out.write_str(&spell(&rest[..end]))?;
+            } else {
+                self.out.write_str(&rest[..end])?;
+            }
+            rest = &rest[end..];
+        }
+        Ok(())
+    }
+
+    pub(crate) fn comma_separated<T: Unparse>(&mut self, items: &[T]) -> fmt::Result {
+        for (idx, item) in items.iter().enumerate() {
+            if idx > 0 {
+                self.write_str(", ")?;
+            }
+            item.unparse(self)?;
+        }
+        Ok(())
+    }
+}
+impl Write for SqlWriter<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.out.write_str(s)
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+impl<T: Unparse + ?Sized> Unparse for Box<T> {
+    fn unparse(&self, w: &mut SqlWriter) -> fmt::Result {
+        (**self).unparse(w)
+    }
+}
+
+/// Implements `Display` for nodes by unparsing them with upper case keywords.
+macro_rules! display_unparsed {
+    ($($node:ty),* $(,)?) => {
+        $(
+            impl std::fmt::Display for $node {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    $crate::ast::unparse::Unparse::unparse(
+                        self,
+                        &mut $crate::ast::unparse::SqlWriter::new(f),
+                    )
+                }
+            }
+        )*
+    };
+}
+pub(crate) use display_unparsed;
@@ -0,0 +1,866 @@
+//! Traversal of the AST.
+//!
+//! A [`Visitor`] only overrides the hooks it is interested in, the [`Visit`] impls
+//! walk the whole tree including subqueries, CTEs and join conditions. Every
+//! `pre_visit_*` hook is called before the children of the node are visited and
+//! every `post_visit_*` hook after.
+//!
+//! ```
+//! use sqlparser_nom::ast::table_ref::TableName;
+//! use sqlparser_nom::ast::visitor::{Visit, Visitor};
+//!
+//! #[derive(Default)]
+//! struct Tables(Vec<String>);
+//! impl Visitor for Tables {
+//!     fn pre_visit_table_name(&mut self, name: &TableName) {
+//!         self.0.push(name.to_string());
+//!     }
+//! }
+//!
+//! let stmt = sqlparser_nom::parse_query("SELECT * FROM a JOIN (SELECT * FROM b) AS c").unwrap();
+//! let mut tables = Tables::default();
+//! stmt.visit(&mut tables);
+//! assert_eq!(tables.0, vec!["a", "b"]);
+//! ```
+
+use super::data_type::DataType;
+use super::expr::{
+    CaseWhen, Expr, FunctionArg, Pattern, Window, WindowFrame, WindowFrameBound, WindowSpec,
+};
+use super::set_expr::{NamedWindowDef, SelectItem, SetExpr, WildcardOptions};
+use super::statement::{Cte, OrderByExpr, SelectStatement, Statement, TableAlias, With};
+use super::table_ref::{JoinCondition, TableName, TableRef};
+use super::Ident;
+
+/// Hooks called while walking the AST, all of them do nothing by default.
+#[allow(unused_variables)]
+pub trait Visitor {
+    fn pre_visit_statement(&mut self, stmt: &SelectStatement) {}
+    fn post_visit_statement(&mut self, stmt: &SelectStatement) {}
+
+    fn pre_visit_set_expr(&mut self, set_expr: &SetExpr) {}
+    fn post_visit_set_expr(&mut self, set_expr: &SetExpr) {}
+
+    fn pre_visit_table_ref(&mut self, table_ref: &TableRef) {}
+    fn post_visit_table_ref(&mut self, table_ref: &TableRef) {}
+
+    fn pre_visit_table_name(&mut self, name: &TableName) {}
+    fn post_visit_table_name(&mut self, name: &TableName) {}
+
+    fn pre_visit_expr(&mut self, expr: &Expr) {}
+    fn post_visit_expr(&mut self, expr: &Expr) {}
+
+    /// Called on `Expr::Function`, after `pre_visit_expr`
+    fn pre_visit_function(&mut self, function: &Expr) {}
+    /// Called on `Expr::Function`, before `post_visit_expr`
+    fn post_visit_function(&mut self, function: &Expr) {}
+
+    fn pre_visit_window(&mut self, window: &Window) {}
+    fn post_visit_window(&mut self, window: &Window) {}
+
+    fn pre_visit_ident(&mut self, ident: &Ident) {}
+    fn post_visit_ident(&mut self, ident: &Ident) {}
+}
+
+/// Hooks called while walking the AST mutably, all of them do nothing by default.
+///
+/// The children of a node are visited after its `pre_visit_*` hook, so they can
+/// be replaced there and the replacement is walked as well.
+#[allow(unused_variables)]
+pub trait VisitorMut {
+    fn pre_visit_statement(&mut self, stmt: &mut SelectStatement) {}
+    fn post_visit_statement(&mut self, stmt: &mut SelectStatement) {}
+
+    fn pre_visit_set_expr(&mut self, set_expr: &mut SetExpr) {}
+    fn post_visit_set_expr(&mut self, set_expr: &mut SetExpr) {}
+
+    fn pre_visit_table_ref(&mut self, table_ref: &mut TableRef) {}
+    fn post_visit_table_ref(&mut self, table_ref: &mut TableRef) {}
+
+    fn pre_visit_table_name(&mut self, name: &mut TableName) {}
+    fn post_visit_table_name(&mut self, name: &mut TableName) {}
+
+    fn pre_visit_expr(&mut self, expr: &mut Expr) {}
+    fn post_visit_expr(&mut self, expr: &mut Expr) {}
+
+    /// Called on `Expr::Function`, after `pre_visit_expr`
+    fn pre_visit_function(&mut self, function: &mut Expr) {}
+    /// Called on `Expr::Function`, before `post_visit_expr`
+    fn post_visit_function(&mut self, function: &mut Expr) {}
+
+    fn pre_visit_window(&mut self, window: &mut Window) {}
+    fn post_visit_window(&mut self, window: &mut Window) {}
+
+    fn pre_visit_ident(&mut self, ident: &mut Ident) {}
+    fn post_visit_ident(&mut self, ident: &mut Ident) {}
+}
+
+/// A node that can be walked by a [`Visitor`]
+pub trait Visit {
+    fn visit<V: Visitor>(&self, visitor: &mut V);
+}
+
+/// A node that can be walked by a [`VisitorMut`]
+pub trait VisitMut {
+    fn visit_mut<V: VisitorMut>(&mut self, visitor: &mut V);
+}
+
+impl<T: Visit> Visit for Option<T> {
+    fn visit<V: Visitor>(&self, visitor: &mut V) {
+        if let Some(node) = self {
+            node.visit(visitor);
+        }
+    }
+}
+impl<T: VisitMut> VisitMut for Option<T> {
+    fn visit_mut<V: VisitorMut>(&mut self, visitor: &mut V) {
+        if let Some(node) = self {
+            node.visit_mut(visitor);
+        }
+    }
+}
+
+impl<T: Visit> Visit for Vec<T> {
+    fn visit<V: Visitor>(&self, visitor: &mut V) {
+        for node in self {
+            node.visit(visitor);
+        }
+    }
+}
+impl<T: VisitMut> VisitMut for Vec<T> {
+    fn visit_mut<V: VisitorMut>(&mut self, visitor: &mut V) {
+        for node in self {
+            node.visit_mut(visitor);
+        }
+    }
+}
+
+impl<T: Visit> Visit for Box<T> {
+    fn visit<V: Visitor>(&self, visitor: &mut V) {
+        (**self).visit(visitor);
+    }
+}
+impl<T: VisitMut> VisitMut for Box<T> {
+    fn visit_mut<V: VisitorMut>(&mut self, visitor: &mut V) {
+        (**self).visit_mut(visitor);
+    }
+}
+
+impl Visit for Statement {
+    fn visit<V: Visitor>(&self, visitor: &mut V) {
+        match self {
+            Self::Select(stmt) => stmt.visit(visitor),
+            Self::Error { .. } => {}
+        }
+    }
+}
+impl VisitMut for Statement {
+    fn visit_mut<V: VisitorMut>(&mut self, visitor: &mut V) {
+        match self {
+            Self::Select(stmt) => stmt.visit_mut(visitor),
+            Self::Error { .. } => {}
+        }
+    }
+}
+
+impl Visit for SelectStatement {
+    fn visit<V: Visitor>(&self, visitor: &mut V) {
+        visitor.pre_visit_statement(self);
+        self.with.visit(visitor);
+        self.body.visit(visitor);
+        self.order_by.visit(visitor);
+        self.limit.visit(visitor);
+        self.offset.visit(visitor);
+        visitor.post_visit_statement(self);
+    }
+}
+impl VisitMut for SelectStatement {
+    fn visit_mut<V: VisitorMut>(&mut self, visitor: &mut V) {
+        visitor.pre_visit_statement(self);
+        self.with.visit_mut(visitor);
+        self.body.visit_mut(visitor);
+        self.order_by.visit_mut(visitor);
+        self.limit.visit_mut(visitor);
+        self.offset.visit_mut(visitor);
+        visitor.post_visit_statement(self);
+    }
+}
+
+impl Visit for With {
+    fn visit<V: Visitor>(&self, visitor: &mut V) {
+        self.cte_tables.visit(visitor);
+    }
+}
+impl VisitMut for With {
+    fn visit_mut<V: VisitorMut>(&mut self, visitor: &mut V) {
+        self.cte_tables.visit_mut(visitor);
+    }
+}
+
+impl Visit for Cte {
+    fn visit<V: Visitor>(&self, visitor: &mut V) {
+        self.alias.visit(visitor);
+        self.query.visit(visitor);
+    }
+}
+impl VisitMut for Cte {
+    fn visit_mut<V: VisitorMut>(&mut self, visitor: &mut V) {
+        self.alias.visit_mut(visitor);
+        self.query.visit_mut(visitor);
+    }
+}
+
+impl Visit for TableAlias {
+    fn visit<V: Visitor>(&self, visitor: &mut V) {
+        self.name.visit(visitor);
+        self.columns.visit(visitor);
+    }
+}
+impl VisitMut for TableAlias {
+    fn visit_mut<V: VisitorMut>(&mut self, visitor: &mut V) {
+        self.name.visit_mut(visitor);
+        self.columns.visit_mut(visitor);
+    }
+}
+
+impl Visit for OrderByExpr {
+    fn visit<V: Visitor>(&self, visitor: &mut V) {
+        self.expr.visit(visitor);
+    }
+}
+impl VisitMut for OrderByExpr {
+    fn visit_mut<V: VisitorMut>(&mut self, visitor: &mut V) {
+        self.expr.visit_mut(visitor);
+    }
+}
+
+impl Visit for SetExpr {
+    fn visit<V: Visitor>(&self, visitor: &mut V) {
+        visitor.pre_visit_set_expr(self);
+        match self {
+            Self::Select {
+                projection,
+                from,
+                selection,
+                group_by,
+                having,
+                named_windows,
+                ..
+            } => {
+                projection.visit(visitor);
+                from.visit(visitor);
+                selection.visit(visitor);
+                group_by.visit(visitor);
+                having.visit(visitor);
+                named_windows.visit(visitor);
+            }
+            Self::Values { rows, .. } => rows.visit(visitor),
+            Self::SetOperation { left, right, .. } => {
+                left.visit(visitor);
+                right.visit(visitor);
+            }
+            Self::Query(query) => query.visit(visitor),
+        }
+        visitor.post_visit_set_expr(self);
+    }
+}
+impl VisitMut for SetExpr {
+    fn visit_mut<V: VisitorMut>(&mut self, visitor: &mut V) {
+        visitor.pre_visit_set_expr(self);
+        match self {
+            Self::Select {
+                projection,
+                from,
+                selection,
+                group_by,
+                having,
+                named_windows,
+                ..
+            } => {
+                projection.visit_mut(visitor);
+                from.visit_mut(visitor);
+                selection.visit_mut(visitor);
+                group_by.visit_mut(visitor);
+                having.visit_mut(visitor);
+                named_windows.visit_mut(visitor);
+            }
+            Self::Values { rows, .. } => rows.visit_mut(visitor),
+            Self::SetOperation { left, right, .. } => {
+                left.visit_mut(visitor);
+                right.visit_mut(visitor);
+            }
+            Self::Query(query) => query.visit_mut(visitor),
+        }
+        visitor.post_visit_set_expr(self);
+    }
+}
+
+impl Visit for SelectItem {
+    fn visit<V: Visitor>(&self, visitor: &mut V) {
+        match self {
+            Self::UnnamedExpr(expr) => expr.visit(visitor),
+            Self::ExprWithAlias { expr, alias, .. } => {
+                expr.visit(visitor);
+                alias.visit(visitor);
+            }
+            Self::Wildcard(options) => options.visit(visitor),
+        }
+    }
+}
+impl VisitMut for SelectItem {
+    fn visit_mut<V: VisitorMut>(&mut self, visitor: &mut V) {
+        match self {
+            Self::UnnamedExpr(expr) => expr.visit_mut(visitor),
+            Self::ExprWithAlias { expr, alias, .. } => {
+                expr.visit_mut(visitor);
+                alias.visit_mut(visitor);
+            }
+            Self::Wildcard(options) => options.visit_mut(visitor),
+        }
+    }
+}
+
+impl Visit for WildcardOptions {
+    fn visit<V: Visitor>(&self, visitor: &mut V) {
+        self.exclude.visit(visitor);
+        self.except.visit(visitor);
+    }
+}
+impl VisitMut for WildcardOptions {
+    fn visit_mut<V: VisitorMut>(&mut self, visitor: &mut V) {
+        self.exclude.visit_mut(visitor);
+        self.except.visit_mut(visitor);
+    }
+}
+
+impl Visit for NamedWindowDef {
+    fn visit<V: Visitor>(&self, visitor: &mut V) {
+        self.name.visit(visitor);
+        self.spec.visit(visitor);
+    }
+}
+impl VisitMut for NamedWindowDef {
+    fn visit_mut<V: VisitorMut>(&mut self, visitor: &mut V) {
+        self.name.visit_mut(visitor);
+        self.spec.visit_mut(visitor);
+    }
+}
+
+impl Visit for TableRef {
+    fn visit<V: Visitor>(&self, visitor: &mut V) {
+        visitor.pre_visit_table_ref(self);
+        match self {
+            Self::BaseTable { name, alias, .. } => {
+                name.visit(visitor);
+                alias.visit(visitor);
+            }
+            Self::Subquery {
+                subquery, alias, ..
+            } => {
+                subquery.visit(visitor);
+                alias.visit(visitor);
+            }
+            Self::Join {
+                condition,
+                left,
+                right,
+                ..
+            } => {
+                left.visit(visitor);
+                right.visit(visitor);
+                condition.visit(visitor);
+            }
+        }
+        visitor.post_visit_table_ref(self);
+    }
+}
+impl VisitMut for TableRef {
+    fn visit_mut<V: VisitorMut>(&mut self, visitor: &mut V) {
+        visitor.pre_visit_table_ref(self);
+        match self {
+            Self::BaseTable { name, alias, .. } => {
+                name.visit_mut(visitor);
+                alias.visit_mut(visitor);
+            }
+            Self::Subquery {
+                subquery, alias, ..
+            } => {
+                subquery.visit_mut(visitor);
+                alias.visit_mut(visitor);
+            }
+            Self::Join {
+                condition,
+                left,
+                right,
+                ..
+            } => {
+                left.visit_mut(visitor);
+                right.visit_mut(visitor);
+                condition.visit_mut(visitor);
+            }
+        }
+        visitor.post_visit_table_ref(self);
+    }
+}
+
+impl Visit for TableName {
+    fn visit<V: Visitor>(&self, visitor: &mut V) {
+        visitor.pre_visit_table_name(self);
+        self.database.visit(visitor);
+        self.table.visit(visitor);
+        visitor.post_visit_table_name(self);
+    }
+}
+impl VisitMut for TableName {
+    fn visit_mut<V: VisitorMut>(&mut self, visitor: &mut V) {
+        visitor.pre_visit_table_name(self);
+        self.database.visit_mut(visitor);
+        self.table.visit_mut(visitor);
+        visitor.post_visit_table_name(self);
+    }
+}
+
+impl Visit for JoinCondition {
+    fn visit<V: Visitor>(&self, visitor: &mut V) {
+        match self {
+            Self::On(expr) => expr.visit(visitor),
+            Self::None => {}
+        }
+    }
+}
+impl VisitMut for JoinCondition {
+    fn visit_mut<V: VisitorMut>(&mut self, visitor: &mut V) {
+        match self {
+            Self::On(expr) => expr.visit_mut(visitor),
+            Self::None => {}
+        }
+    }
+}
+
+impl Visit for Expr {
+    fn visit<V: Visitor>(&self, visitor: &mut V) {
+        visitor.pre_visit_expr(self);
+        if let Self::Function { .. } = self {
+            visitor.pre_visit_function(self);
+        }
+        match self {
+            Self::ColumnRef {
+                database,
+                table,
+                column,
+                ..
+            } => {
+                database.visit(visitor);
+                table.visit(visitor);
+                column.visit(visitor);
+            }
+            Self::Literal { .. } | Self::Interval { .. } | Self::Error { .. } => {}
+            Self::Subquery { subquery, .. } | Self::Exists { subquery, .. } => {
+                subquery.visit(visitor)
+            }
+            Self::Cast {
+                expr, data_type, ..
+            } => {
+                expr.visit(visitor);
+                data_type.visit(visitor);
+            }
+            Self::UnaryOp { expr, .. }
+            | Self::IsNull { expr, .. }
+            | Self::IsTrue { expr, .. }
+            | Self::IsFalse { expr, .. }
+            | Self::IsUnknown { expr, .. } => expr.visit(visitor),
+            Self::BinaryOp { left, right, .. } | Self::IsDistinctFrom { left, right, .. } => {
+                left.visit(visitor);
+                right.visit(visitor);
+            }
+            Self::Function {
+                name,
+                args,
+                order_by,
+                limit,
+                within_group,
+                filter,
+                over,
+                ..
+            } => {
+                name.visit(visitor);
+                args.visit(visitor);
+                order_by.visit(visitor);
+                limit.visit(visitor);
+                within_group.visit(visitor);
+                filter.visit(visitor);
+                over.visit(visitor);
+            }
+            Self::InSubquery { expr, subquery, .. } => {
+                expr.visit(visitor);
+                subquery.visit(visitor);
+            }
+            Self::InList { expr, list, .. } => {
+                expr.visit(visitor);
+                list.visit(visitor);
+            }
+            Self::Case {
+                operand,
+                whens,
+                else_result,
+                ..
+            } => {
+                operand.visit(visitor);
+                whens.visit(visitor);
+                else_result.visit(visitor);
+            }
+            Self::Between {
+                expr, low, high, ..
+            } => {
+                expr.visit(visitor);
+                low.visit(visitor);
+                high.visit(visitor);
+            }
+            Self::Like { expr, pattern, .. }
+            | Self::ILike { expr, pattern, .. }
+            | Self::SimilarTo { expr, pattern, .. }
+            | Self::RLike { expr, pattern, .. } => {
+                expr.visit(visitor);
+                pattern.visit(visitor);
+            }
+        }
+        if let Self::Function { .. } = self {
+            visitor.post_visit_function(self);
+        }
+        visitor.post_visit_expr(self);
+    }
+}
+impl VisitMut for Expr {
+    fn visit_mut<V: VisitorMut>(&mut self, visitor: &mut V) {
+        visitor.pre_visit_expr(self);
+        if let Self::Function { .. } = self {
+            visitor.pre_visit_function(self);
+        }
+        match self {
+            Self::ColumnRef {
+                database,
+                table,
+                column,
+                ..
+            } => {
+                database.visit_mut(visitor);
+                table.visit_mut(visitor);
+                column.visit_mut(visitor);
+            }
+            Self::Literal { .. } | Self::Interval { .. } | Self::Error { .. } => {}
+            Self::Subquery { subquery, .. } | Self::Exists { subquery, .. } => {
+                subquery.visit_mut(visitor)
+            }
+            Self::Cast {
+                expr, data_type, ..
+            } => {
+                expr.visit_mut(visitor);
+                data_type.visit_mut(visitor);
+            }
+            Self::UnaryOp { expr, .. }
+            | Self::IsNull { expr, .. }
+            | Self::IsTrue { expr, .. }
+            | Self::IsFalse { expr, .. }
+            | Self::IsUnknown { expr, .. } => expr.visit_mut(visitor),
+            Self::BinaryOp { left, right, .. } | Self::IsDistinctFrom { left, right, .. } => {
+                left.visit_mut(visitor);
+                right.visit_mut(visitor);
+            }
+            Self::Function {
+                name,
+                args,
+                order_by,
+                limit,
+                within_group,
+                filter,
+                over,
+                ..
+            } => {
+                name.visit_mut(visitor);
+                args.visit_mut(visitor);
+                order_by.visit_mut(visitor);
+                limit.visit_mut(visitor);
+                within_group.visit_mut(visitor);
+                filter.visit_mut(visitor);
+                over.visit_mut(visitor);
+            }
+            Self::InSubquery { expr, subquery, .. } => {
+                expr.visit_mut(visitor);
+                subquery.visit_mut(visitor);
+            }
+            Self::InList { expr, list, .. } => {
+                expr.visit_mut(visitor);
+                list.visit_mut(visitor);
+            }
+            Self::Case {
+                operand,
+                whens,
+                else_result,
+                ..
+            } => {
+                operand.visit_mut(visitor);
+                whens.visit_mut(visitor);
+                else_result.visit_mut(visitor);
+            }
+            Self::Between {
+                expr, low, high, ..
+            } => {
+                expr.visit_mut(visitor);
+                low.visit_mut(visitor);
+                high.visit_mut(visitor);
+            }
+            Self::Like { expr, pattern, .. }
+            | Self::ILike { expr, pattern, .. }
+            | Self::SimilarTo { expr, pattern, .. }
+            | Self::RLike { expr, pattern, .. } => {
+                expr.visit_mut(visitor);
+                pattern.visit_mut(visitor);
+            }
+        }
+        if let Self::Function { .. } = self {
+            visitor.post_visit_function(self);
+        }
+        visitor.post_visit_expr(self);
+    }
+}
+
+impl Visit for DataType {
+    fn visit<V: Visitor>(&self, visitor: &mut V) {
+        match self {
+            Self::Array { element } => element.visit(visitor),
+            Self::Struct { fields } => {
+                for field in fields {
+                    field.name.visit(visitor);
+                    field.data_type.visit(visitor);
+                }
+            }
+            Self::Map { key, value } => {
+                key.visit(visitor);
+                value.visit(visitor);
+            }
+            Self::Custom { schema, name } => {
+                schema.visit(visitor);
+                name.visit(visitor);
+            }
+            _ => {}
+        }
+    }
+}
+impl VisitMut for DataType {
+    fn visit_mut<V: VisitorMut>(&mut self, visitor: &mut V) {
+        match self {
+            Self::Array { element } => element.visit_mut(visitor),
+            Self::Struct { fields } => {
+                for field in fields {
+                    field.name.visit_mut(visitor);
+                    field.data_type.visit_mut(visitor);
+                }
+            }
+            Self::Map { key, value } => {
+                key.visit_mut(visitor);
+                value.visit_mut(visitor);
+            }
+            Self::Custom { schema, name } => {
+                schema.visit_mut(visitor);
+                name.visit_mut(visitor);
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Visit for CaseWhen {
+    fn visit<V: Visitor>(&self, visitor: &mut V) {
+        self.condition.visit(visitor);
+        self.result.visit(visitor);
+    }
+}
+impl VisitMut for CaseWhen {
+    fn visit_mut<V: VisitorMut>(&mut self, visitor: &mut V) {
+        self.condition.visit_mut(visitor);
+        self.result.visit_mut(visitor);
+    }
+}
+
+impl Visit for Pattern {
+    fn visit<V: Visitor>(&self, visitor: &mut V) {
+        match self {
+            Self::Expr(expr) => expr.visit(visitor),
+            Self::Any(patterns) | Self::All(patterns) => patterns.visit(visitor),
+        }
+    }
+}
+impl VisitMut for Pattern {
+    fn visit_mut<V: VisitorMut>(&mut self, visitor: &mut V) {
+        match self {
+            Self::Expr(expr) => expr.visit_mut(visitor),
+            Self::Any(patterns) | Self::All(patterns) => patterns.visit_mut(visitor),
+        }
+    }
+}
+
+impl Visit for FunctionArg {
+    fn visit<V: Visitor>(&self, visitor: &mut V) {
+        match self {
+            Self::Wildcard { .. } => {}
+            Self::Expr(expr) => expr.visit(visitor),
+        }
+    }
+}
+impl VisitMut for FunctionArg {
+    fn visit_mut<V: VisitorMut>(&mut self, visitor: &mut V) {
+        match self {
+            Self::Wildcard { .. } => {}
+            Self::Expr(expr) => expr.visit_mut(visitor),
+        }
+    }
+}
+
+impl Visit for Window {
+    fn visit<V: Visitor>(&self, visitor: &mut V) {
+        visitor.pre_visit_window(self);
+        match self {
+            Self::WindowRef(name) => name.visit(visitor),
+            Self::WindowSpec(spec) => spec.visit(visitor),
+        }
+        visitor.post_visit_window(self);
+    }
+}
+impl VisitMut for Window {
+    fn visit_mut<V: VisitorMut>(&mut self, visitor: &mut V) {
+        visitor.pre_visit_window(self);
+        match self {
+            Self::WindowRef(name) => name.visit_mut(visitor),
+            Self::WindowSpec(spec) => spec.visit_mut(visitor),
+        }
+        visitor.post_visit_window(self);
+    }
+}
+
+impl Visit for WindowSpec {
+    fn visit<V: Visitor>(&self, visitor: &mut V) {
+        self.window_name.visit(visitor);
+        self.partition_by.visit(visitor);
+        self.order_by.visit(visitor);
+        self.frame.visit(visitor);
+    }
+}
+impl VisitMut for WindowSpec {
+    fn visit_mut<V: VisitorMut>(&mut self, visitor: &mut V) {
+        self.window_name.visit_mut(visitor);
+        self.partition_by.visit_mut(visitor);
+        self.order_by.visit_mut(visitor);
+        self.frame.visit_mut(visitor);
+    }
+}
+
+impl Visit for WindowFrame {
+    fn visit<V: Visitor>(&self, visitor: &mut V) {
+        for bound in std::iter::once(&self.start).chain(&self.end) {
+            if let WindowFrameBound::Preceding(offset) | WindowFrameBound::Following(offset) = bound
+            {
+                offset.visit(visitor);
+            }
+        }
+    }
+}
+impl VisitMut for WindowFrame {
+    fn visit_mut<V: VisitorMut>(&mut self, visitor: &mut V) {
+        for bound in std::iter::once(&mut self.start).chain(&mut self.end) {
+            if let WindowFrameBound::Preceding(offset) | WindowFrameBound::Following(offset) = bound
+            {
+                offset.visit_mut(visitor);
+            }
+        }
+    }
+}
+
+impl Visit for Ident {
+    fn visit<V: Visitor>(&self, visitor: &mut V) {
+        visitor.pre_visit_ident(self);
+        visitor.post_visit_ident(self);
+    }
+}
+impl VisitMut for Ident {
+    fn visit_mut<V: VisitorMut>(&mut self, visitor: &mut V) {
+        visitor.pre_visit_ident(self);
+        visitor.post_visit_ident(self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_query;
+
+    #[derive(Default)]
+    struct Collector {
+        tables: Vec<String>,
+        functions: Vec<String>,
+        windows: usize,
+        depth: usize,
+        max_depth: usize,
+    }
+    impl Visitor for Collector {
+        fn pre_visit_statement(&mut self, _: &SelectStatement) {
+            self.depth += 1;
+            self.max_depth = self.max_depth.max(self.depth);
+        }
+        fn post_visit_statement(&mut self, _: &SelectStatement) {
+            self.depth -= 1;
+        }
+        fn pre_visit_table_name(&mut self, name: &TableName) {
+            self.tables.push(name.to_string());
+        }
+        fn pre_visit_function(&mut self, function: &Expr) {
+            if let Expr::Function { name, .. } = function {
+                self.functions.push(name.to_string());
+            }
+        }
+        fn pre_visit_window(&mut self, _: &Window) {
+            self.windows += 1;
+        }
+    }
+
+    #[test]
+    pub fn test_visitor() {
+        let stmt = parse_query(
+            "WITH x AS (SELECT a FROM t1) \
+            SELECT count(*) OVER (PARTITION BY max(b)) FROM x JOIN t2 ON x.a IN (SELECT a FROM db.t3) \
+            WHERE EXISTS (SELECT * FROM (SELECT sum(c) FROM t4) AS y)",
+        )
+        .unwrap();
+        let mut collector = Collector::default();
+        stmt.visit(&mut collector);
+        assert_eq!(collector.tables, vec!["t1", "x", "t2", "db.t3", "t4"]);
+        assert_eq!(collector.functions, vec!["count", "max", "sum"]);
+        assert_eq!(collector.windows, 1);
+        assert_eq!(collector.max_depth, 3);
+        assert_eq!(collector.depth, 0);
+    }
+
+    struct Rename;
+    impl VisitorMut for Rename {
+        fn pre_visit_table_name(&mut self, name: &mut TableName) {
+            if name.database.is_none() {
+                name.database = Some(Ident::new("db"));
+            }
+        }
+        fn post_visit_expr(&mut self, expr: &mut Expr) {
+            if let Expr::ColumnRef { column, .. } = expr {
+                column.value = column.value.to_uppercase();
+            }
+        }
+    }
+
+    #[test]
+    pub fn test_visitor_mut() {
+        let mut stmt =
+            parse_query("SELECT a, t.b FROM t JOIN (SELECT c FROM u) AS v ON t.a = v.c").unwrap();
+        stmt.visit_mut(&mut Rename);
+        assert_eq!(
+            stmt.to_string(),
+            "SELECT A, t.B FROM db.t INNER JOIN (SELECT C FROM db.u) AS v ON t.A = v.C"
+        );
+    }
+}
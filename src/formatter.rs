@@ -0,0 +1,692 @@
+//! Pretty-printer of queries.
+//!
+//! Every clause starts on its own line, lists and conditions are broken into
+//! lines when they don't fit in the line width, and subqueries of `WITH` and
+//! `FROM` are indented. Expressions are printed like their `Display`, with the
+//! keywords cased as they are written. The output only depends on the AST and
+//! the options, so formatting it again gives the same sql.
+
+use std::collections::HashMap;
+
+use crate::ast::expr::{BinaryOp, Expr, WindowSpec};
+use crate::ast::set_expr::{NamedWindowDef, SetExpr};
+use crate::ast::statement::{SelectStatement, With};
+use crate::ast::table_ref::{JoinCondition, TableRef};
+use crate::ast::unparse::{SqlWriter, Unparse};
+use crate::parser::error::PError;
+use crate::parser::token::{TokenKind, Tokenizer};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeywordCase {
+    Upper,
+    Lower,
+    /// Keeps the spelling of the first occurrence of each keyword in the source sql,
+    /// the keywords missing from the source follow the case of its first keyword
+    Preserve,
+}
+
+#[derive(Debug, Clone)]
+pub struct FormatOptions {
+    /// Number of spaces of an indentation level
+    pub indent: usize,
+    pub keyword_case: KeywordCase,
+    /// Lists and conditions longer than this are broken into lines
+    pub line_width: usize,
+    /// Puts the commas of a broken list at the start of the lines
+    pub leading_commas: bool,
+    /// Always puts each select item on its own line
+    pub one_item_per_line: bool,
+    /// Aligns the `ON` of the joins of a `FROM` clause into a column
+    pub align_joins: bool,
+}
+impl Default for FormatOptions {
+    fn default() -> Self {
+        FormatOptions {
+            indent: 4,
+            keyword_case: KeywordCase::Upper,
+            line_width: 80,
+            leading_commas: false,
+            one_item_per_line: false,
+            align_joins: false,
+        }
+    }
+}
+
+/// Formats a query. There is no source sql to preserve the keyword case of,
+/// so `KeywordCase::Preserve` keeps them upper case.
+pub fn format(stmt: &SelectStatement, options: &FormatOptions) -> String {
+    Formatter {
+        options,
+        spellings: HashMap::new(),
+        lower_by_default: false,
+    }
+    .format(stmt)
+}
+
+/// Parses and formats a query.
+pub fn format_sql(sql: &str, options: &FormatOptions) -> Result<String, PError> {
+    let stmt = crate::parse_query(sql)?;
+    let mut spellings = HashMap::new();
+    let mut lower_by_default = None;
+    for token in Tokenizer::new(sql).map_while(Result::ok) {
+        // words that aren't reserved, like `NULLS` or `ILIKE`, are lexed as identifiers
+        if token.kind.is_keyword() || token.kind == TokenKind::Ident {
+            let text = token.text();
+            if token.kind.is_keyword() {
+                lower_by_default.get_or_insert(text == text.to_ascii_lowercase());
+            }
+            spellings
+                .entry(text.to_ascii_uppercase())
+                .or_insert_with(|| text.to_string());
+        }
+    }
+    Ok(Formatter {
+        options,
+        spellings,
+        lower_by_default: lower_by_default.unwrap_or(false),
+    }
+    .format(&stmt))
+}
+
+struct Formatter<'a> {
+    options: &'a FormatOptions,
+    // Upper case keyword to its spelling in the source sql
+    spellings: HashMap<String, String>,
+    lower_by_default: bool,
+}
+impl Formatter<'_> {
+    fn format(&self, stmt: &SelectStatement) -> String {
+        self.statement(stmt, 0).join("\n")
+    }
+
+    /// The spelling of an upper case keyword
+    fn spell(&self, keyword: &str) -> String {
+        match self.options.keyword_case {
+            KeywordCase::Upper => keyword.to_string(),
+            KeywordCase::Lower => keyword.to_ascii_lowercase(),
+            KeywordCase::Preserve => match self.spellings.get(keyword) {
+                Some(spelling) => spelling.clone(),
+                None if self.lower_by_default => keyword.to_ascii_lowercase(),
+                None => keyword.to_string(),
+            },
+        }
+    }
+
+    /// The sql of a node, with the keywords cased as they are written
+    fn sql(&self, node: &impl Unparse) -> String {
+        let mut sql = String::new();
+        let spell = |keyword: &str| self.spell(keyword);
+        node.unparse(&mut SqlWriter::with_spelling(&mut sql, &spell))
+            .expect("writing to a String doesn't fail");
+        sql
+    }
+
+    /// Upper case keywords and punctuation of a clause, like `ORDER BY`
+    fn keyword(&self, sql: &str) -> String {
+        let mut cased = String::new();
+        let spell = |keyword: &str| self.spell(keyword);
+        SqlWriter::with_spelling(&mut cased, &spell)
+            .keyword(sql)
+            .expect("writing to a String doesn't fail");
+        cased
+    }
+
+    /// Lines of a statement starting at column `indent`
+    fn statement(&self, stmt: &SelectStatement, indent: usize) -> Vec<String> {
+        let mut lines = vec![];
+        if let Some(with) = &stmt.with {
+            lines.extend(self.with(with, indent));
+        }
+        lines.extend(self.set_expr(&stmt.body, indent));
+        if !stmt.order_by.is_empty() {
+            let items = stmt.order_by.iter().map(|o| vec![self.sql(o)]).collect();
+            lines.extend(self.list("ORDER BY", items, indent, false));
+        }
+        if let Some(limit) = &stmt.limit {
+            lines.push(format!("{} {}", self.keyword("LIMIT"), self.sql(limit)));
+        }
+        if let Some(offset) = &stmt.offset {
+            lines.push(format!("{} {}", self.keyword("OFFSET"), self.sql(offset)));
+        }
+        lines
+    }
+
+    fn with(&self, with: &With, indent: usize) -> Vec<String> {
+        let mut lines = vec![];
+        let mut head = if with.recursive {
+            "WITH RECURSIVE "
+        } else {
+            "WITH "
+        };
+        for (idx, cte) in with.cte_tables.iter().enumerate() {
+            lines.push(format!(
+                "{}{}{}",
+                self.keyword(head),
+                self.sql(&cte.alias),
+                self.keyword(" AS (")
+            ));
+            lines.extend(self.nested(&cte.query, indent));
+            if idx + 1 < with.cte_tables.len() && !self.options.leading_commas {
+                lines.push("),".to_string());
+                head = "";
+            } else {
+                lines.push(")".to_string());
+                head = ", ";
+            }
+        }
+        lines
+    }
+
+    fn set_expr(&self, set_expr: &SetExpr, indent: usize) -> Vec<String> {
+        match set_expr {
+            SetExpr::Select {
+                distinct,
+                projection,
+                from,
+                selection,
+                group_by,
+                having,
+                named_windows,
+                ..
+            } => {
+                let mut lines = self.list(
+                    if *distinct {
+                        "SELECT DISTINCT"
+                    } else {
+                        "SELECT"
+                    },
+                    projection.iter().map(|item| vec![self.sql(item)]).collect(),
+                    indent,
+                    self.options.one_item_per_line,
+                );
+                if let Some(from) = from {
+                    lines.extend(self.from(from, indent));
+                }
+                if let Some(selection) = selection {
+                    lines.extend(self.condition("WHERE", selection, indent));
+                }
+                if !group_by.is_empty() {
+                    let items = group_by.iter().map(|e| vec![self.sql(e)]).collect();
+                    lines.extend(self.list("GROUP BY", items, indent, false));
+                }
+                if let Some(having) = having {
+                    lines.extend(self.condition("HAVING", having, indent));
+                }
+                if !named_windows.is_empty() {
+                    let items = named_windows
+                        .iter()
+                        .map(|def| self.named_window_def(def, indent + self.options.indent))
+                        .collect();
+                    lines.extend(self.list("WINDOW", items, indent, false));
+                }
+                lines
+            }
+            SetExpr::Values { rows, .. } => {
+                let items = rows
+                    .iter()
+                    .map(|row| vec![format!("({})", self.join(row))])
+                    .collect();
+                self.list("VALUES", items, indent, false)
+            }
+            SetExpr::SetOperation {
+                op,
+                quantifier,
+                left,
+                right,
+                ..
+            } => {
+                let bp = set_expr.binding_power();
+                let mut lines = self.set_operand(left, left.binding_power() < bp, indent);
+                match quantifier {
+                    Some(quantifier) => {
+                        lines.push(format!("{} {}", self.sql(op), self.sql(quantifier)))
+                    }
+                    None => lines.push(self.sql(op)),
+                }
+                lines.extend(self.set_operand(right, right.binding_power() <= bp, indent));
+                lines
+            }
+            SetExpr::Query(query) => {
+                let mut lines = vec!["(".to_string()];
+                lines.extend(self.nested(query, indent));
+                lines.push(")".to_string());
+                lines
+            }
+        }
+    }
+
+    /// An operand of a set operation, indented in parentheses if it needs them
+    fn set_operand(&self, set_expr: &SetExpr, parens: bool, indent: usize) -> Vec<String> {
+        if !parens {
+            return self.set_expr(set_expr, indent);
+        }
+        let mut lines = vec!["(".to_string()];
+        lines.extend(self.indented(self.set_expr(set_expr, indent + self.options.indent)));
+        lines.push(")".to_string());
+        lines
+    }
+
+    /// A `FROM` clause with each join of the left-deep join tree on its own line.
+    fn from(&self, table_ref: &TableRef, indent: usize) -> Vec<String> {
+        let mut joins = vec![];
+        let mut first = table_ref;
+        while let TableRef::Join {
+            op,
+            condition,
+            left,
+            right,
+            ..
+        } = first
+        {
+            joins.push((op, right.as_ref(), condition));
+            first = left;
+        }
+        joins.reverse();
+
+        let mut lines = self.table_ref(first, indent);
+        lines[0].insert_str(0, &self.keyword("FROM "));
+        let heads = joins
+            .iter()
+            .map(|(op, right, _)| {
+                let mut right = self.table_ref(right, indent);
+                right[0].insert_str(0, &format!("{} ", self.sql(*op)));
+                right
+            })
+            .collect::<Vec<_>>();
+        let width = heads
+            .iter()
+            .filter(|head| head.len() == 1)
+            .map(|head| head[0].chars().count())
+            .max()
+            .unwrap_or(0);
+        for ((_, _, condition), mut head) in joins.into_iter().zip(heads) {
+            if let JoinCondition::On(expr) = condition {
+                let single_line = head.len() == 1;
+                let last = head.last_mut().expect("table ref has lines");
+                if self.options.align_joins && single_line {
+                    let padding = width - last.chars().count();
+                    last.push_str(&" ".repeat(padding));
+                }
+                last.push_str(&self.keyword(" ON "));
+                last.push_str(&self.sql(expr));
+            }
+            lines.extend(head);
+        }
+        lines
+    }
+
+    fn table_ref(&self, table_ref: &TableRef, indent: usize) -> Vec<String> {
+        match table_ref {
+            TableRef::Subquery {
+                subquery, alias, ..
+            } => {
+                let mut lines = vec!["(".to_string()];
+                lines.extend(self.nested(subquery, indent));
+                match alias {
+                    Some(alias) => {
+                        lines.push(format!("{}{}", self.keyword(") AS "), self.sql(alias)))
+                    }
+                    None => lines.push(")".to_string()),
+                }
+                lines
+            }
+            // a join on the right side of another join
+            TableRef::Join { .. } => vec![format!("({})", self.sql(table_ref))],
+            TableRef::BaseTable { .. } => vec![self.sql(table_ref)],
+        }
+    }
+
+    /// A condition on one line if it fits, otherwise with each operand of
+    /// the top-level `AND`/`OR` chain on its own line.
+    fn condition(&self, head: &str, expr: &Expr, indent: usize) -> Vec<String> {
+        let head = self.keyword(head);
+        let line = format!("{head} {}", self.sql(expr));
+        let op = match expr {
+            Expr::BinaryOp { op, .. } if matches!(op, BinaryOp::And | BinaryOp::Or) => *op,
+            _ => return vec![line],
+        };
+        if self.fits(&line, indent) {
+            return vec![line];
+        }
+        let mut operands = vec![];
+        let mut left = expr;
+        while let Expr::BinaryOp {
+            left: l,
+            op: o,
+            right,
+            ..
+        } = left
+        {
+            if *o != op {
+                break;
+            }
+            operands.push(right.as_ref());
+            left = l;
+        }
+        let bp = expr.binding_power();
+        let mut lines = vec![format!(
+            "{head} {}",
+            self.operand(left, left.binding_power() < bp)
+        )];
+        let pad = " ".repeat(self.options.indent);
+        for right in operands.into_iter().rev() {
+            lines.push(format!(
+                "{pad}{} {}",
+                self.sql(&op),
+                self.operand(right, right.binding_power() <= bp)
+            ));
+        }
+        lines
+    }
+
+    fn named_window_def(&self, def: &NamedWindowDef, indent: usize) -> Vec<String> {
+        let line = self.sql(def);
+        if self.fits(&line, indent) {
+            return vec![line];
+        }
+        let mut lines = vec![format!("{}{}", self.sql(&def.name), self.keyword(" AS ("))];
+        lines.extend(self.indented(self.window_spec(&def.spec)));
+        lines.push(")".to_string());
+        lines
+    }
+
+    fn window_spec(&self, spec: &WindowSpec) -> Vec<String> {
+        let mut lines = vec![];
+        if let Some(window_name) = &spec.window_name {
+            lines.push(self.sql(window_name));
+        }
+        if !spec.partition_by.is_empty() {
+            lines.push(format!(
+                "{}{}",
+                self.keyword("PARTITION BY "),
+                self.join(&spec.partition_by)
+            ));
+        }
+        if !spec.order_by.is_empty() {
+            lines.push(format!(
+                "{}{}",
+                self.keyword("ORDER BY "),
+                self.join(&spec.order_by)
+            ));
+        }
+        if let Some(frame) = &spec.frame {
+            lines.push(self.sql(frame));
+        }
+        lines
+    }
+
+    /// A list of items after `head`, on one line if it fits, otherwise with each
+    /// item on its own line. An item has more than one line if it was broken itself.
+    fn list(
+        &self,
+        head: &str,
+        items: Vec<Vec<String>>,
+        indent: usize,
+        one_item_per_line: bool,
+    ) -> Vec<String> {
+        let head = self.keyword(head);
+        if !one_item_per_line && items.iter().all(|item| item.len() == 1) {
+            let line = format!(
+                "{head} {}",
+                items
+                    .iter()
+                    .map(|item| item[0].as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+            if self.fits(&line, indent) {
+                return vec![line];
+            }
+        }
+        let mut lines = vec![head];
+        let pad = " ".repeat(self.options.indent);
+        let count = items.len();
+        for (idx, item) in items.into_iter().enumerate() {
+            let last_line = item.len() - 1;
+            for (line_idx, line) in item.into_iter().enumerate() {
+                let mut line = match (self.options.leading_commas, line_idx, idx) {
+                    (true, 0, 0) | (true, 1.., _) => format!("{pad}  {line}"),
+                    (true, 0, _) => format!("{pad}, {line}"),
+                    (false, _, _) => format!("{pad}{line}"),
+                };
+                if !self.options.leading_commas && line_idx == last_line && idx + 1 < count {
+                    line.push(',');
+                }
+                lines.push(line);
+            }
+        }
+        lines
+    }
+
+    fn nested(&self, stmt: &SelectStatement, indent: usize) -> Vec<String> {
+        self.indented(self.statement(stmt, indent + self.options.indent))
+    }
+
+    fn indented(&self, lines: Vec<String>) -> Vec<String> {
+        let pad = " ".repeat(self.options.indent);
+        lines
+            .into_iter()
+            .map(|line| format!("{pad}{line}"))
+            .collect()
+    }
+
+    fn fits(&self, line: &str, indent: usize) -> bool {
+        indent + line.chars().count() <= self.options.line_width
+    }
+
+    fn operand(&self, expr: &Expr, parens: bool) -> String {
+        if parens {
+            format!("({})", self.sql(expr))
+        } else {
+            self.sql(expr)
+        }
+    }
+
+    fn join<T: Unparse>(&self, items: &[T]) -> String {
+        items
+            .iter()
+            .map(|item| self.sql(item))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format, format_sql, FormatOptions, KeywordCase};
+
+    #[test]
+    pub fn test_format() {
+        let sql = "with x as (select a, b from t where a > 1) \
+            select a, count(*) as cnt from x join y on x.a = y.a left join (select c from z) as w on w.c = x.b \
+            where x.a > 1 and y.some_long_column_name = z.another_long_column_name or x.b = 3 \
+            group by a having count(*) > 1 order by a desc limit 10";
+        assert_eq!(
+            format_sql(sql, &FormatOptions::default()).unwrap(),
+            "\
+WITH x AS (
+    SELECT a, b
+    FROM t
+    WHERE a > 1
+)
+SELECT a, count(*) AS cnt
+FROM x
+INNER JOIN y ON x.a = y.a
+LEFT OUTER JOIN (
+    SELECT c
+    FROM z
+) AS w ON w.c = x.b
+WHERE x.a > 1 AND y.some_long_column_name = z.another_long_column_name
+    OR x.b = 3
+GROUP BY a
+HAVING count(*) > 1
+ORDER BY a DESC
+LIMIT 10"
+        );
+        assert_eq!(
+            format(&crate::parse_query(sql).unwrap(), &FormatOptions::default()),
+            format_sql(sql, &FormatOptions::default()).unwrap()
+        );
+    }
+
+    #[test]
+    pub fn test_format_options() {
+        let sql = "SELECT a, b AS c FROM t1 JOIN t2 ON t1.a = t2.a CROSS JOIN t3 \
+            LEFT JOIN some_long_table ON t1.b = some_long_table.b \
+            WINDOW w1 AS (PARTITION BY a, b ORDER BY c, d, e), w2 AS (ORDER BY d)";
+        let options = FormatOptions {
+            indent: 2,
+            keyword_case: KeywordCase::Lower,
+            line_width: 40,
+            leading_commas: true,
+            one_item_per_line: true,
+            align_joins: true,
+        };
+        assert_eq!(
+            format_sql(sql, &options).unwrap(),
+            "\
+select
+    a
+  , b as c
+from t1
+inner join t2                   on t1.a = t2.a
+cross join t3
+left outer join some_long_table on t1.b = some_long_table.b
+window
+    w1 as (
+      partition by a, b
+      order by c, d, e
+    )
+  , w2 as (order by d)"
+        );
+    }
+
+    #[test]
+    pub fn test_preserve_keyword_case() {
+        let sql = "Select a From t join u on t.a = u.a where EXISTS (select 1)";
+        let options = FormatOptions {
+            keyword_case: KeywordCase::Preserve,
+            ..Default::default()
+        };
+        assert_eq!(
+            format_sql(sql, &options).unwrap(),
+            "Select a\nFrom t\nINNER join u on t.a = u.a\nwhere EXISTS (Select 1)"
+        );
+    }
+
+    #[test]
+    pub fn test_keyword_case() {
+        // columns named like keywords are identifiers and keep their spelling
+        let sql =
+            "select t.End, t.Select, t.rows, T.Filter from t where t.from > 1 order by t.Order";
+        for keyword_case in [
+            KeywordCase::Upper,
+            KeywordCase::Lower,
+            KeywordCase::Preserve,
+        ] {
+            let options = FormatOptions {
+                keyword_case,
+                ..Default::default()
+            };
+            let formatted = format_sql(sql, &options).unwrap();
+            assert_eq!(
+                crate::parse_query(&formatted).unwrap(),
+                crate::parse_query(sql).unwrap(),
+                "{formatted}"
+            );
+            assert!(formatted.contains("t.End, t.Select, t.rows, T.Filter"));
+        }
+
+        // words that aren't reserved are cased too
+        let sql = "SELECT count(*) FILTER (WHERE a ILIKE 'X') OVER (ORDER BY b NULLS FIRST ROWS UNBOUNDED PRECEDING), \
+            c IS UNKNOWN, CAST(d AS INTERVAL DAY) FROM t";
+        let options = FormatOptions {
+            keyword_case: KeywordCase::Lower,
+            line_width: 200,
+            ..Default::default()
+        };
+        assert_eq!(
+            format_sql(sql, &options).unwrap(),
+            "select count(*) filter (where a ilike 'X') over (order by b nulls first rows unbounded preceding), \
+                c is unknown, cast(d as interval day)\nfrom t"
+        );
+    }
+
+    #[test]
+    pub fn test_format_set_operation() {
+        let sql = "select a from t union all (select a from u order by a limit 1) \
+            except (select a from v intersect select a from w) order by a";
+        assert_eq!(
+            format_sql(sql, &FormatOptions::default()).unwrap(),
+            "\
+SELECT a
+FROM t
+UNION ALL
+(
+    SELECT a
+    FROM u
+    ORDER BY a
+    LIMIT 1
+)
+EXCEPT
+SELECT a
+FROM v
+INTERSECT
+SELECT a
+FROM w
+ORDER BY a"
+        );
+        assert_eq!(
+            format_sql(
+                "SELECT 1 INTERSECT (SELECT 2 UNION SELECT 3)",
+                &FormatOptions::default()
+            )
+            .unwrap(),
+            "SELECT 1\nINTERSECT\n(\n    SELECT 2\n    UNION\n    SELECT 3\n)"
+        );
+    }
+
+    #[test]
+    pub fn test_idempotent() {
+        let sqls = [
+            "WITH RECURSIVE x (a) AS (SELECT 1), y AS (SELECT * FROM x) SELECT DISTINCT * EXCLUDE (b) FROM y",
+            "SELECT a FROM (SELECT b FROM (SELECT c FROM t) AS u JOIN v ON u.b = v.b) AS w \
+                WHERE a = 1 AND b = 2 AND (c = 3 OR d = 4) AND e NOT IN (SELECT e FROM t) LIMIT 1 OFFSET 2",
+            "select count(*) over (partition by a order by b), sum(c) over w from t \
+                window w as (partition by some_long_column, another_long_column order by yet_another_column)",
+            "select percentile_cont(0.5) within group (order by some_long_column desc nulls last) filter (where another_long_column > 0) from t",
+            "select sum(a) over (w order by b rows between unbounded preceding and current row exclude ties) from t \
+                window w as (partition by some_long_column range between interval '1' day preceding and current row)",
+            "select a, b, c, d, e, f, g, h, i, j, k, l, m, n, o, p, q, r, s, t, u, v, w, x, y, z from t \
+                group by a, b, c, d, e, f, g, h, i, j, k, l, m, n, o, p, q, r, s, t, u, v, w, x, y, z",
+            "(select a from t limit 1) union select b from u except (select c from v union select d from w)",
+            "select * from (values (1, 'one'), (2, 'two'), (3, 'three'), (4, 'four'), (5, 'five')) as v (n, name)",
+        ];
+        let options = [
+            FormatOptions::default(),
+            FormatOptions {
+                indent: 2,
+                keyword_case: KeywordCase::Lower,
+                line_width: 30,
+                leading_commas: true,
+                one_item_per_line: true,
+                align_joins: true,
+            },
+            FormatOptions {
+                keyword_case: KeywordCase::Preserve,
+                line_width: 0,
+                ..Default::default()
+            },
+        ];
+        for sql in sqls {
+            for options in &options {
+                let formatted = format_sql(sql, options).unwrap();
+                assert_eq!(
+                    crate::parse_query(&formatted).unwrap(),
+                    crate::parse_query(sql).unwrap()
+                );
+                assert_eq!(format_sql(&formatted, options).unwrap(), formatted);
+            }
+        }
+    }
+}
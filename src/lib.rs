@@ -1,19 +1,141 @@
-use crate::ast::statement::SelectStatement;
-use crate::parser::error::PError;
-use crate::parser::statement::select_stmt;
-use crate::parser::tokenize_sql;
+use nom::branch::alt;
+use nom::combinator::{opt, peek};
+use nom::sequence::{terminated, tuple};
+
+use crate::ast::statement::{SelectStatement, Statement};
+use crate::parser::common::{consumed_span, match_token};
+use crate::parser::error::{Backtrace, PError, ScriptError};
+use crate::parser::statement::{select_stmt, statement};
+use crate::parser::token::{SemiColon, Token, Tokenizer, EOI};
+use crate::parser::{tokenize_sql, Input};
 
 pub mod ast;
+pub mod formatter;
 pub mod parser;
 
+/// Parses a single query, which may be followed by a `;`.
 pub fn parse_query(sql: &str) -> Result<SelectStatement, PError> {
-    let tokens = tokenize_sql(sql);
-    match select_stmt(&tokens) {
-        Ok((_, stmt)) => Ok(stmt),
-        Err(e) => match e {
-            nom::Err::Error(e) => Err(e),
-            nom::Err::Failure(e) => Err(e),
-            _ => Err(PError("Failed to parse query sql".to_string())),
-        },
+    let tokens = tokenize_sql(sql)?;
+    let backtrace = Backtrace::new();
+    let result = tuple((select_stmt, opt(match_token(SemiColon)), match_token(EOI)))(Input::new(
+        &tokens, &backtrace,
+    ));
+    result
+        .map(|(_, (stmt, _, _))| stmt)
+        .map_err(|e| backtrace.report(e))
+}
+
+/// Parses a script of statements separated by `;`, empty statements are skipped.
+pub fn parse_statements(sql: &str) -> Result<Vec<Statement>, ScriptError> {
+    let tokens = tokenize_sql(sql).map_err(|error| ScriptError {
+        index: statement_index(sql, error.span.start),
+        error,
+    })?;
+    let mut stmts = vec![];
+    let mut rest = &tokens[..];
+    loop {
+        while rest[0].kind == SemiColon {
+            rest = &rest[1..];
+        }
+        if rest[0].kind == EOI {
+            return Ok(stmts);
+        }
+        let backtrace = Backtrace::new();
+        let result = terminated(
+            statement,
+            peek(alt((match_token(SemiColon), match_token(EOI)))),
+        )(Input::new(rest, &backtrace));
+        match result {
+            Ok((i, stmt)) => {
+                rest = &rest[rest.len() - i.len()..];
+                stmts.push(stmt);
+            }
+            Err(e) => {
+                return Err(ScriptError {
+                    index: stmts.len(),
+                    error: backtrace.report(e),
+                })
+            }
+        }
+    }
+}
+
+/// Parses a script of statements separated by `;` like `parse_statements`, but
+/// recovers from syntax errors instead of stopping at the first one.
+///
+/// A statement or a part of it that failed to parse is replaced with an error
+/// placeholder, and every error is returned as a diagnostic in source order.
+pub fn parse_statements_with_recovery(sql: &str) -> (Vec<Statement>, Vec<PError>) {
+    let mut diagnostics = vec![];
+    let mut tokens = vec![];
+    for token in Tokenizer::new(sql) {
+        match token {
+            Ok(token) => tokens.push(token),
+            Err(error) => diagnostics.push(error),
+        }
+    }
+    tokens.push(Token {
+        source: sql,
+        kind: EOI,
+        span: sql.len()..sql.len(),
+    });
+
+    let mut stmts = vec![];
+    let mut rest = &tokens[..];
+    loop {
+        while rest[0].kind == SemiColon {
+            rest = &rest[1..];
+        }
+        if rest[0].kind == EOI {
+            break;
+        }
+        let backtrace = Backtrace::recovering();
+        let start = Input::new(rest, &backtrace);
+        let result = statement(start);
+        let consumed = match result {
+            Ok((i, stmt)) => {
+                stmts.push(stmt);
+                if !matches!(i[0].kind, SemiColon | EOI) {
+                    let error = PError::unexpected(i, [SemiColon, EOI]);
+                    backtrace.push_diagnostic(backtrace.take().unwrap_or(error));
+                }
+                rest.len() - i.len()
+            }
+            Err(e) => {
+                backtrace.push_diagnostic(backtrace.report(e));
+                0
+            }
+        };
+        // skip the rest of the statement
+        rest = &rest[consumed..];
+        while !matches!(rest[0].kind, SemiColon | EOI) {
+            rest = &rest[1..];
+        }
+        if consumed == 0 {
+            stmts.push(Statement::Error {
+                span: consumed_span(start, Input::new(rest, &backtrace)),
+            });
+        }
+        diagnostics.extend(backtrace.into_diagnostics());
+    }
+    diagnostics.sort_by_key(|e| e.span.start);
+    (stmts, diagnostics)
+}
+
+/// Index of the non-empty statement that `pos` falls into.
+fn statement_index(sql: &str, pos: usize) -> usize {
+    let mut index = 0;
+    let mut empty = true;
+    for token in Tokenizer::new(sql)
+        .map_while(Result::ok)
+        .take_while(|token| token.span.start < pos)
+    {
+        if token.kind != SemiColon {
+            empty = false;
+        } else if !empty {
+            index += 1;
+            empty = true;
+        }
     }
+    index
 }
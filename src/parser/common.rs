@@ -1,7 +1,7 @@
 use nom::multi::{separated_list0, separated_list1};
 use nom::Slice;
 
-use crate::ast::Ident;
+use crate::ast::{Ident, NodeSpan, Span};
 
 use super::{
     error::PError,
@@ -16,59 +16,165 @@ pub enum AffixKind {
     Infix,
 }
 
-pub fn match_text(text: &'static str) -> impl FnMut(Input) -> IResult<&Token> {
-    move |i| match i.get(0).filter(|token| token.text() == text) {
+pub fn match_text<'a>(text: &'static str) -> impl FnMut(Input<'a>) -> IResult<'a, &'a Token<'a>> {
+    move |i| match i.tokens.first().filter(|token| token.text() == text) {
         Some(token) => Ok((i.slice(1..), token)),
-        None => Err(nom::Err::Error(PError(format!(
-            "text {text} does not match"
-        )))),
+        None => Err(nom::Err::Error(PError::message(
+            i,
+            format!("text {text} does not match"),
+        ))),
     }
 }
 
-pub fn match_token(kind: TokenKind) -> impl FnMut(Input) -> IResult<&Token> {
-    move |i| match i.get(0).filter(|token| token.kind == kind) {
+/// Matches an identifier spelled as `word` in any case, for words that aren't keywords.
+pub fn match_word<'a>(word: &'static str) -> impl FnMut(Input<'a>) -> IResult<'a, &'a Token<'a>> {
+    move |i| match i
+        .tokens
+        .first()
+        .filter(|token| token.kind == TokenKind::Ident && token.text().eq_ignore_ascii_case(word))
+    {
+        Some(token) => Ok((i.slice(1..), token)),
+        None => Err(nom::Err::Error(PError::unexpected(i, [TokenKind::Ident]))),
+    }
+}
+
+/// A name after a `.`, where a keyword can't be meant, so it names a column
+/// like `end` in `t.end`.
+pub fn qualified_ident(i: Input) -> IResult<Ident> {
+    match i.tokens.first().filter(|token| {
+        token.kind == TokenKind::Ident
+            || (token.kind.is_keyword()
+                && token.text().starts_with(|c: char| c.is_ascii_alphabetic()))
+    }) {
+        Some(token) => Ok((
+            i.slice(1..),
+            Ident {
+                value: token.text().to_string(),
+                span: token.span.clone().into(),
+            },
+        )),
+        None => Err(nom::Err::Error(PError::unexpected(i, [TokenKind::Ident]))),
+    }
+}
+
+pub fn match_token<'a>(kind: TokenKind) -> impl FnMut(Input<'a>) -> IResult<'a, &'a Token<'a>> {
+    move |i| match i.tokens.first().filter(|token| token.kind == kind) {
         Some(token) => Ok((i.slice(1..), token)),
-        None => Err(nom::Err::Error(PError(format!(
-            "token kind {kind} does not match"
-        )))),
+        None => Err(nom::Err::Error(PError::unexpected(i, [kind]))),
     }
 }
 
 pub fn ident(i: Input) -> IResult<Ident> {
     match i
-        .get(0)
+        .tokens
+        .first()
         .filter(|token| (token.kind == TokenKind::Ident) && (!token.kind.is_keyword()))
     {
         Some(token) => Ok((
             i.slice(1..),
             Ident {
                 value: token.text().to_string(),
+                span: token.span.clone().into(),
             },
         )),
-        None => PError::from("ident should not be a keyword"),
+        None => Err(nom::Err::Error(PError::unexpected(i, [TokenKind::Ident]))),
     }
 }
 
 pub fn comma_separated_list0<'a, T>(
     item: impl FnMut(Input<'a>) -> IResult<'a, T>,
 ) -> impl FnMut(Input<'a>) -> IResult<'a, Vec<T>> {
-    separated_list0(match_text(","), item)
+    separated_list0(match_token(TokenKind::Comma), item)
 }
 
 pub fn comma_separated_list1<'a, T>(
     item: impl FnMut(Input<'a>) -> IResult<'a, T>,
 ) -> impl FnMut(Input<'a>) -> IResult<'a, Vec<T>> {
-    separated_list1(match_text(","), item)
+    separated_list1(match_token(TokenKind::Comma), item)
+}
+
+/// Span of the tokens consumed from `start` up to `rest`, or an empty span at the
+/// next token if nothing was consumed.
+pub fn consumed_span(start: Input, rest: Input) -> NodeSpan {
+    let consumed = &start.tokens[..start.len() - rest.len()];
+    match (consumed.first(), consumed.last()) {
+        (Some(first), Some(last)) => Span::new(first.span.start, last.span.end).into(),
+        _ => Span::new(start.span().start, start.span().start).into(),
+    }
+}
+
+/// Runs `parser` and returns its output along with the span of the tokens it consumed.
+pub fn spanned<'a, O>(
+    mut parser: impl FnMut(Input<'a>) -> IResult<'a, O>,
+) -> impl FnMut(Input<'a>) -> IResult<'a, (O, NodeSpan)> {
+    move |i| {
+        let (rest, output) = parser(i)?;
+        Ok((rest, (output, consumed_span(i, rest))))
+    }
+}
+
+/// In a recovering parse, a failure of `parser`, or a parse that isn't followed by
+/// one of the `sync` tokens, is recorded as a diagnostic. The tokens up to the next
+/// `sync` token are then skipped and replaced with the `placeholder` node built
+/// from the span of the skipped tokens.
+pub fn recover<'a, O>(
+    mut parser: impl FnMut(Input<'a>) -> IResult<'a, O>,
+    sync: &'static [TokenKind],
+    placeholder: impl Fn(NodeSpan) -> O,
+) -> impl FnMut(Input<'a>) -> IResult<'a, O> {
+    move |i| {
+        if !i.backtrace.is_recovering() {
+            return parser(i);
+        }
+        let error = match parser(i) {
+            Ok((rest, output)) => match rest.first() {
+                Some(token) if !sync.contains(&token.kind) => {
+                    PError::unexpected(rest, sync.iter().copied())
+                }
+                _ => return Ok((rest, output)),
+            },
+            Err(nom::Err::Error(e) | nom::Err::Failure(e)) => e,
+            Err(e) => return Err(e),
+        };
+        i.backtrace
+            .push_diagnostic(i.backtrace.take().unwrap_or(error));
+        let rest = skip_to(i, sync);
+        Ok((rest, placeholder(consumed_span(i, rest))))
+    }
+}
+
+/// Skips tokens until one of `sync` outside of parentheses, an unmatched `)`, a `;` or the end of input.
+pub fn skip_to<'a>(i: Input<'a>, sync: &[TokenKind]) -> Input<'a> {
+    let mut depth = 0;
+    let end = i
+        .iter()
+        .position(|token| match token.kind {
+            TokenKind::SemiColon | TokenKind::EOI => true,
+            TokenKind::LParen => {
+                depth += 1;
+                false
+            }
+            TokenKind::RParen if depth > 0 => {
+                depth -= 1;
+                false
+            }
+            TokenKind::RParen => true,
+            kind => depth == 0 && sync.contains(&kind),
+        })
+        .unwrap_or(i.len());
+    i.slice(end..)
 }
 
 #[cfg(test)]
 mod tests {
     #[test]
     pub fn test_ident() {
-        use crate::parser::tokenize_sql;
+        use crate::parser::error::Backtrace;
+        use crate::parser::{tokenize_sql, Input};
 
-        let tokens = tokenize_sql(")");
-        let result = super::ident(&tokens);
+        let tokens = tokenize_sql(")").unwrap();
+        let backtrace = Backtrace::new();
+        let result = super::ident(Input::new(&tokens, &backtrace));
         assert!(result.is_err());
     }
 }
@@ -0,0 +1,285 @@
+use nom::branch::alt;
+use nom::combinator::opt;
+use nom::sequence::tuple;
+use nom::Parser;
+
+use crate::ast::data_type::{DataType, StructField};
+use crate::ast::expr::Number;
+use crate::parser::common::{
+    comma_separated_list1, ident, match_token, match_word, qualified_ident, spanned,
+};
+use crate::parser::error::PError;
+use crate::parser::expr::interval_unit;
+use crate::parser::token::*;
+use crate::parser::{IResult, Input};
+
+/// Type names are not keywords, a name that isn't a known type is a custom type.
+pub fn data_type(i: Input) -> IResult<DataType> {
+    let (mut i, mut data_type) = alt((
+        // a qualified name is always a custom type, like `pg_catalog.int4`
+        tuple((ident, match_token(Dot), qualified_ident)).map(|(schema, _, name)| {
+            DataType::Custom {
+                schema: Some(schema),
+                name,
+            }
+        }),
+        numeric_type,
+        character_type,
+        temporal_type,
+        nested_type,
+        ident.map(|name| DataType::Custom { schema: None, name }),
+    ))(i)?;
+    // `INT[]` or `INT[3]`, the size is ignored like PostgreSQL does
+    while let Ok((rest, _)) = tuple((
+        match_token(LBracket),
+        opt(match_token(LiteralInteger)),
+        match_token(RBracket),
+    ))(i)
+    {
+        data_type = DataType::Array {
+            element: Box::new(data_type),
+        };
+        i = rest;
+    }
+    Ok((i, data_type))
+}
+
+fn numeric_type(i: Input) -> IResult<DataType> {
+    alt((
+        match_word("TINYINT").map(|_| DataType::TinyInt),
+        alt((match_word("SMALLINT"), match_word("INT2"))).map(|_| DataType::SmallInt),
+        alt((match_word("INT"), match_word("INTEGER"), match_word("INT4"))).map(|_| DataType::Int),
+        alt((match_word("BIGINT"), match_word("INT8"))).map(|_| DataType::BigInt),
+        alt((match_word("REAL"), match_word("FLOAT4"))).map(|_| DataType::Real),
+        alt((
+            tuple((match_word("DOUBLE"), opt(match_word("PRECISION")))).map(|_| ()),
+            match_word("FLOAT8").map(|_| ()),
+        ))
+        .map(|_| DataType::Double),
+        tuple((match_word("FLOAT"), opt(length)))
+            .map(|(_, precision)| DataType::Float { precision }),
+        tuple((
+            alt((
+                match_word("DECIMAL"),
+                match_word("NUMERIC"),
+                match_word("DEC"),
+            )),
+            opt(tuple((
+                match_token(LParen),
+                unsigned,
+                opt(tuple((match_token(Comma), unsigned))),
+                match_token(RParen),
+            ))),
+        ))
+        .map(|(_, modifiers)| match modifiers {
+            Some((_, precision, scale, _)) => DataType::Decimal {
+                precision: Some(precision),
+                scale: scale.map(|(_, scale)| scale),
+            },
+            None => DataType::Decimal {
+                precision: None,
+                scale: None,
+            },
+        }),
+        alt((match_word("BOOLEAN"), match_word("BOOL"))).map(|_| DataType::Boolean),
+    ))(i)
+}
+
+fn character_type(i: Input) -> IResult<DataType> {
+    alt((
+        tuple((
+            alt((
+                tuple((
+                    alt((match_word("CHARACTER"), match_word("CHAR"))),
+                    match_word("VARYING"),
+                ))
+                .map(|_| ()),
+                match_word("VARCHAR").map(|_| ()),
+            )),
+            opt(length),
+        ))
+        .map(|(_, length)| DataType::Varchar { length }),
+        tuple((
+            alt((match_word("CHARACTER"), match_word("CHAR"))),
+            opt(length),
+        ))
+        .map(|(_, length)| DataType::Char { length }),
+        match_word("TEXT").map(|_| DataType::Text),
+        match_word("STRING").map(|_| DataType::String),
+    ))(i)
+}
+
+fn temporal_type(i: Input) -> IResult<DataType> {
+    alt((
+        match_word("DATE").map(|_| DataType::Date),
+        tuple((match_word("TIME"), opt(length), opt(time_zone))).map(|(_, precision, tz)| {
+            DataType::Time {
+                precision,
+                with_time_zone: tz.unwrap_or(false),
+            }
+        }),
+        tuple((match_word("TIMESTAMP"), opt(length), opt(time_zone))).map(|(_, precision, tz)| {
+            DataType::Timestamp {
+                precision,
+                with_time_zone: tz.unwrap_or(false),
+            }
+        }),
+        tuple((match_word("TIMESTAMPTZ"), opt(length))).map(|(_, precision)| DataType::Timestamp {
+            precision,
+            with_time_zone: true,
+        }),
+        tuple((
+            match_word("INTERVAL"),
+            opt(tuple((
+                interval_unit,
+                opt(tuple((match_word("TO"), interval_unit))),
+            ))),
+        ))
+        .map(|(_, qualifier)| match qualifier {
+            Some((unit, to)) => DataType::Interval {
+                unit: Some(unit),
+                to: to.map(|(_, to)| to),
+            },
+            None => DataType::Interval {
+                unit: None,
+                to: None,
+            },
+        }),
+    ))(i)
+}
+
+/// `WITH TIME ZONE` or `WITHOUT TIME ZONE`
+fn time_zone(i: Input) -> IResult<bool> {
+    tuple((
+        alt((
+            match_token(WITH).map(|_| true),
+            match_word("WITHOUT").map(|_| false),
+        )),
+        match_word("TIME"),
+        match_word("ZONE"),
+    ))
+    .map(|(with, _, _)| with)
+    .parse(i)
+}
+
+fn nested_type(i: Input) -> IResult<DataType> {
+    alt((
+        tuple((
+            match_word("ARRAY"),
+            match_token(Lt),
+            data_type,
+            match_token(Gt),
+        ))
+        .map(|(_, _, element, _)| DataType::Array {
+            element: Box::new(element),
+        }),
+        tuple((
+            match_word("STRUCT"),
+            match_token(Lt),
+            comma_separated_list1(struct_field),
+            match_token(Gt),
+        ))
+        .map(|(_, _, fields, _)| DataType::Struct { fields }),
+        tuple((
+            match_word("MAP"),
+            match_token(Lt),
+            data_type,
+            match_token(Comma),
+            data_type,
+            match_token(Gt),
+        ))
+        .map(|(_, _, key, _, value, _)| DataType::Map {
+            key: Box::new(key),
+            value: Box::new(value),
+        }),
+    ))(i)
+}
+
+fn struct_field(i: Input) -> IResult<StructField> {
+    spanned(tuple((ident, data_type)))
+        .map(|((name, data_type), span)| StructField {
+            name,
+            data_type,
+            span,
+        })
+        .parse(i)
+}
+
+/// `(n)` of types like `VARCHAR(n)` or `TIMESTAMP(n)`
+fn length(i: Input) -> IResult<u64> {
+    tuple((match_token(LParen), unsigned, match_token(RParen)))
+        .map(|(_, n, _)| n)
+        .parse(i)
+}
+
+fn unsigned(i: Input) -> IResult<u64> {
+    let (rest, token) = match_token(LiteralInteger)(i)?;
+    match Number::new(token.text()).as_u64() {
+        Some(n) => Ok((rest, n)),
+        None => Err(nom::Err::Error(PError::message(i, "integer out of range"))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    pub fn test_data_type() {
+        use super::*;
+        use crate::parser::error::Backtrace;
+        use crate::parser::tokenize_sql;
+
+        let cases = [
+            ("int", "INT"),
+            ("Integer", "INT"),
+            ("int8", "BIGINT"),
+            ("double precision", "DOUBLE PRECISION"),
+            ("float(24)", "FLOAT(24)"),
+            ("numeric", "DECIMAL"),
+            ("decimal(10)", "DECIMAL(10)"),
+            ("DECIMAL(10, 2)", "DECIMAL(10, 2)"),
+            ("character varying(20)", "VARCHAR(20)"),
+            ("char", "CHAR"),
+            ("text", "TEXT"),
+            ("string", "STRING"),
+            ("bool", "BOOLEAN"),
+            ("time without time zone", "TIME"),
+            ("timestamp with time zone", "TIMESTAMP WITH TIME ZONE"),
+            ("timestamptz", "TIMESTAMP WITH TIME ZONE"),
+            ("timestamp(3)", "TIMESTAMP(3)"),
+            ("time(6) with time zone", "TIME(6) WITH TIME ZONE"),
+            ("timestamptz(0)", "TIMESTAMP(0) WITH TIME ZONE"),
+            ("interval", "INTERVAL"),
+            ("interval day", "INTERVAL DAY"),
+            ("interval year to month", "INTERVAL YEAR TO MONTH"),
+            ("Interval Day To Second", "INTERVAL DAY TO SECOND"),
+            ("int[][3]", "INT[][]"),
+            ("ARRAY<varchar(3)>", "VARCHAR(3)[]"),
+            (
+                "STRUCT<a INT, b ARRAY<MAP<TEXT, DATE>>>",
+                "STRUCT<a INT, b MAP<TEXT, DATE>[]>",
+            ),
+            ("my_type", "my_type"),
+            ("foo.bar", "foo.bar"),
+            ("pg_catalog.int4", "pg_catalog.int4"),
+            ("public.end[]", "public.end[]"),
+        ];
+        for (sql, display) in cases {
+            let backtrace = Backtrace::new();
+            let tokens = tokenize_sql(sql).unwrap();
+            let (rest, parsed) = data_type(Input::new(&tokens, &backtrace)).unwrap();
+            assert_eq!(rest[0].kind, EOI, "{sql}");
+            assert_eq!(parsed.to_string(), display);
+
+            let tokens = tokenize_sql(display).unwrap();
+            assert_eq!(
+                data_type(Input::new(&tokens, &backtrace)).unwrap().1,
+                parsed
+            );
+        }
+
+        let backtrace = Backtrace::new();
+        let tokens = tokenize_sql("VARCHAR(99999999999999999999)").unwrap();
+        let (rest, _) = data_type(Input::new(&tokens, &backtrace)).unwrap();
+        assert_eq!(rest[0].kind, LParen);
+    }
+}
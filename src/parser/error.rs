@@ -1,19 +1,332 @@
-use super::{IResult, Input};
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+use std::ops::Range;
+
+use super::token::TokenKind::{self, *};
+use super::Input;
+
+/// A parse error pointing at the offending token of the source sql.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PError {
+    // Left closed, right open
+    pub span: Range<usize>,
+    pub kind: ErrorKind,
+    /// Token kinds that would have been accepted at `span`
+    pub expected: BTreeSet<TokenKind>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// The token found at the error position
+    UnexpectedToken(TokenKind),
+    Lexical(LexError),
+    Message(String),
+}
+
+/// Failures of the tokenizer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LexError {
+    UnterminatedQuote,
+    UnterminatedBlockComment,
+    InvalidCharacter(char),
+    MalformedNumber,
+}
+impl std::fmt::Display for LexError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnterminatedQuote => write!(f, "unterminated quoted string"),
+            Self::UnterminatedBlockComment => write!(f, "unterminated block comment"),
+            Self::InvalidCharacter(c) => write!(f, "invalid character `{c}`"),
+            Self::MalformedNumber => write!(f, "malformed number"),
+        }
+    }
+}
 
-#[derive(Debug)]
-pub struct PError(pub String);
 impl PError {
-    pub fn from<O>(message: &str) -> IResult<O> {
-        Err(nom::Err::Error(PError(message.to_string())))
+    /// Creates an error at the next token of `i` and records it in the backtrace.
+    pub fn unexpected(i: Input, expected: impl IntoIterator<Item = TokenKind>) -> Self {
+        let found = i.first().map_or(EOI, |token| token.kind);
+        let error = PError {
+            span: i.span(),
+            kind: ErrorKind::UnexpectedToken(found),
+            expected: expected.into_iter().collect(),
+        };
+        i.backtrace.track(&error);
+        error
+    }
+
+    /// Creates an error with a custom message at the next token of `i` and records it in the backtrace.
+    pub fn message(i: Input, message: impl Into<String>) -> Self {
+        let error = PError {
+            span: i.span(),
+            kind: ErrorKind::Message(message.into()),
+            expected: BTreeSet::new(),
+        };
+        i.backtrace.track(&error);
+        error
+    }
+
+    pub fn lexical(error: LexError, span: Range<usize>) -> Self {
+        PError {
+            span,
+            kind: ErrorKind::Lexical(error),
+            expected: BTreeSet::new(),
+        }
+    }
+
+    /// Merges two errors, keeping the one reached further into the input.
+    fn merge(self, other: Self) -> Self {
+        if other.span.start > self.span.start {
+            return other;
+        }
+        if other.span.start < self.span.start {
+            return self;
+        }
+        let (mut merged, mut rest) = match (&self.kind, &other.kind) {
+            (ErrorKind::UnexpectedToken(_), ErrorKind::Message(_)) => (other, self),
+            _ => (self, other),
+        };
+        // `alt` merges the errors of every alternative, extend the larger set
+        if merged.expected.len() < rest.expected.len() {
+            std::mem::swap(&mut merged.expected, &mut rest.expected);
+        }
+        merged.expected.extend(rest.expected);
+        merged
+    }
+
+    /// Renders the error with the source line it occurred at and a caret underline.
+    ///
+    /// ```text
+    /// error: unexpected `FROM`, expected one of `)`, `+`, `-`
+    ///  --> 1:11
+    ///   |
+    /// 1 | SELECT (a FROM t
+    ///   |           ^^^^
+    /// ```
+    pub fn render(&self, source: &str) -> String {
+        let start = self.span.start.min(source.len());
+        let end = self.span.end.clamp(start, source.len());
+        let line_start = source[..start].rfind('\n').map_or(0, |pos| pos + 1);
+        let line_end = source[start..]
+            .find('\n')
+            .map_or(source.len(), |pos| start + pos);
+        let line_no = source[..start].matches('\n').count() + 1;
+        let column = source[line_start..start].chars().count() + 1;
+        let line = &source[line_start..line_end];
+        let width = source[start..end.min(line_end)].chars().count().max(1);
+
+        let gutter = " ".repeat(line_no.to_string().len());
+        format!(
+            "error: {self}\n{gutter}--> {line_no}:{column}\n{gutter} |\n{line_no} | {line}\n{gutter} | {}{}",
+            " ".repeat(column - 1),
+            "^".repeat(width)
+        )
+    }
+}
+
+impl std::fmt::Display for PError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.kind {
+            ErrorKind::Lexical(error) => write!(f, "{error}"),
+            ErrorKind::Message(message) => write!(f, "{message}"),
+            ErrorKind::UnexpectedToken(found) => {
+                write!(f, "unexpected {}", describe(*found))?;
+                if !self.expected.is_empty() {
+                    write!(
+                        f,
+                        ", expected {}{}",
+                        if self.expected.len() > 1 {
+                            "one of "
+                        } else {
+                            ""
+                        },
+                        self.expected
+                            .iter()
+                            .map(|kind| describe(*kind))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    )?;
+                }
+                Ok(())
+            }
+        }
     }
 }
 
+impl std::error::Error for PError {}
+
+/// Readable name of a token kind used in error messages.
+fn describe(kind: TokenKind) -> String {
+    let text = match kind {
+        Ident => return "identifier".to_string(),
+        QuotedString => return "string".to_string(),
+        LiteralInteger => return "integer".to_string(),
+        LiteralFloat => return "float".to_string(),
+        EOI => return "end of input".to_string(),
+        Eq => "=",
+        NotEq => "!=",
+        Lt => "<",
+        Gt => ">",
+        LtEq => "<=",
+        GtEq => ">=",
+        Plus => "+",
+        Minus => "-",
+        Multiply => "*",
+        Divide => "/",
+        IntDiv => "//",
+        Modulo => "%",
+        StringConcat => "||",
+        BitwiseAnd => "&",
+        BitwiseOr => "|",
+        Caret => "^",
+        Tilde => "~",
+        ShiftLeft => "<<",
+        DoubleStar => "**",
+        LParen => "(",
+        RParen => ")",
+        LBracket => "[",
+        RBracket => "]",
+        Comma => ",",
+        Dot => ".",
+        DoubleColon => "::",
+        SemiColon => ";",
+        _ => return format!("`{kind}`"),
+    };
+    format!("`{text}`")
+}
+
 impl nom::error::ParseError<Input<'_>> for PError {
-    fn from_error_kind(_: Input<'_>, _: nom::error::ErrorKind) -> Self {
-        PError("parse error".to_string())
+    fn from_error_kind(input: Input<'_>, _: nom::error::ErrorKind) -> Self {
+        PError::unexpected(input, [])
     }
 
     fn append(_: Input<'_>, _: nom::error::ErrorKind, other: Self) -> Self {
         other
     }
+
+    fn or(self, other: Self) -> Self {
+        self.merge(other)
+    }
+}
+
+/// An error of a multi-statement script
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptError {
+    /// Index of the failed statement, starting from 0
+    pub index: usize,
+    pub error: PError,
+}
+impl std::fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "statement {}: {}", self.index, self.error)
+    }
+}
+
+impl std::error::Error for ScriptError {}
+
+/// Keeps the error reached furthest into the input during a parse, so the final
+/// error can list every token kind that would have been accepted there even if
+/// the failing branch was discarded by `opt` or `alt`.
+///
+/// A recovering backtrace also collects the errors the parser recovered from.
+#[derive(Debug, Default)]
+pub struct Backtrace {
+    furthest: RefCell<Option<PError>>,
+    diagnostics: Option<RefCell<Vec<PError>>>,
+}
+impl Backtrace {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn recovering() -> Self {
+        Backtrace {
+            furthest: RefCell::default(),
+            diagnostics: Some(RefCell::default()),
+        }
+    }
+
+    pub fn is_recovering(&self) -> bool {
+        self.diagnostics.is_some()
+    }
+
+    /// Records an error that the parser recovered from.
+    pub fn push_diagnostic(&self, error: PError) {
+        if let Some(diagnostics) = &self.diagnostics {
+            diagnostics.borrow_mut().push(error);
+        }
+    }
+
+    pub fn into_diagnostics(self) -> Vec<PError> {
+        self.diagnostics.map_or(vec![], RefCell::into_inner)
+    }
+
+    /// Takes the furthest error, so the following errors are tracked from scratch.
+    pub fn take(&self) -> Option<PError> {
+        self.furthest.borrow_mut().take()
+    }
+
+    pub fn track(&self, error: &PError) {
+        let mut furthest = self.furthest.borrow_mut();
+        *furthest = Some(match furthest.take() {
+            Some(prev) => prev.merge(error.clone()),
+            None => error.clone(),
+        });
+    }
+
+    pub fn furthest(&self) -> Option<PError> {
+        self.furthest.borrow().clone()
+    }
+
+    /// The error to report for a failed parse, the furthest one knows all the tokens expected there.
+    pub fn report(&self, error: nom::Err<PError>) -> PError {
+        match error {
+            nom::Err::Error(e) | nom::Err::Failure(e) => self.furthest().unwrap_or(e),
+            nom::Err::Incomplete(_) => unreachable!("token parsers never ask for more input"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    pub fn test_render() {
+        use crate::parse_query;
+
+        let sql = "WITH x AS (SELECT a FROM t WHERE)\nSELECT * FROM x";
+        let error = parse_query(sql).unwrap_err();
+        assert_eq!(error.span, 32..33);
+        assert_eq!(
+            error.render(sql),
+            "\
+error: unexpected `)`, expected one of identifier, string, integer, float, `+`, `-`, `~`, `(`, `CASE`, `CAST`, `EXISTS`, `FALSE`, `NOT`, `NULL`, `SELECT`, `TRUE`
+ --> 1:33
+  |
+1 | WITH x AS (SELECT a FROM t WHERE)
+  |                                 ^"
+        );
+    }
+
+    #[test]
+    pub fn test_expected_tokens() {
+        use super::ErrorKind;
+        use crate::parse_query;
+        use crate::parser::token::TokenKind::*;
+
+        let error = parse_query("SELECT (a FROM t").unwrap_err();
+        assert_eq!(error.span, 10..14);
+        assert_eq!(error.kind, ErrorKind::UnexpectedToken(FROM));
+        assert!(error.expected.contains(&RParen));
+        assert!(error.expected.contains(&Plus));
+
+        let error = parse_query("SELECT\n\n").unwrap_err();
+        assert_eq!(error.span, 8..8);
+        assert_eq!(error.kind, ErrorKind::UnexpectedToken(EOI));
+        assert!(error.expected.contains(&Multiply));
+
+        let error =
+            parse_query("WITH x AS (SELECT * FROM (t1 JOIN t2) AS t) SELECT * FROM x").unwrap_err();
+        assert_eq!(error.span, 38..40);
+        assert_eq!(error.to_string(), "joined table should not have an alias");
+    }
 }
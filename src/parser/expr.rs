@@ -1,35 +1,40 @@
-use nom::combinator::opt;
+use nom::combinator::{not, opt, success};
+use nom::multi::many1;
+use nom::sequence::preceded;
 use nom::{branch::alt, sequence::tuple};
 use nom::{Parser, Slice};
 
-use crate::ast::expr::{BinaryOp, Expr, FunctionArg, Literal, UnaryOp, Window, WindowSpec};
-use crate::parser::common::{comma_separated_list0, AffixKind, MIN_PRECEDENCE};
+use crate::ast::expr::{
+    BinaryOp, CaseWhen, CastKind, Expr, FunctionArg, IntervalUnit, Literal, NullTreatment, Number,
+    Pattern, UnaryOp, Window, WindowFrame, WindowFrameBound, WindowFrameExclude, WindowFrameUnits,
+    WindowSpec,
+};
+use crate::ast::precedence;
+use crate::parser::common::{
+    comma_separated_list0, consumed_span, spanned, AffixKind, MIN_PRECEDENCE,
+};
+use crate::parser::data_type::data_type;
 use crate::parser::error::PError;
 use crate::parser::statement::{order_by_expr, select_stmt};
 use crate::parser::token::*;
 
 use super::common::comma_separated_list1;
 use super::{
-    common::{ident, match_token},
+    common::{ident, match_token, match_word, qualified_ident},
     IResult, Input,
 };
 
 pub fn expr(i: Input) -> IResult<Expr> {
-    match pratt_parse(i, MIN_PRECEDENCE) {
-        Ok((i, pratt_expr)) => Ok((i, pratt_expr.into_expr())),
-        Err(e) => Err(nom::Err::Error(PError(e))),
-    }
+    pratt_parse(i, MIN_PRECEDENCE).map(|(i, pratt_expr)| (i, pratt_expr.into_expr()))
 }
 
 /// pratt parsing algorithm
-fn pratt_parse(i: Input, lbp: u32) -> Result<(Input, PrattExpr), String> {
+fn pratt_parse(i: Input, lbp: u32) -> IResult<PrattExpr> {
+    let start = i;
     // find a prefix expr
     let (mut i, mut pratt_left) = prefix(i)?;
-    loop {
-        let Ok((_, op)) = pratt_operator(i) else {
-            break;
-        };
-        let Ok(bp) = precedence(op, AffixKind::Infix) else {
+    while let Ok((_, op)) = pratt_operator(i) {
+        let Some(bp) = precedence(op, AffixKind::Infix) else {
             // end of expr
             break;
         };
@@ -39,101 +44,213 @@ fn pratt_parse(i: Input, lbp: u32) -> Result<(Input, PrattExpr), String> {
             break;
         }
         // find infix expr with prefix expr
-        match infix(i, pratt_left.clone()) {
-            Ok(r) => {
-                i = r.0;
-                pratt_left = r.1
-            }
-            Err(e) => {
-                return Err(e);
-            }
-        }
+        (i, pratt_left) = infix(start, i, pratt_left)?;
     }
     Ok((i, pratt_left))
 }
 
 // find prefix expr
-fn prefix(i: Input) -> Result<(Input, PrattExpr), String> {
-    let Some(token) = i.get(0) else {
-        return Err("No token found".to_string());
+fn prefix(i: Input) -> IResult<PrattExpr> {
+    let Some(token) = i.tokens.first() else {
+        return Err(nom::Err::Error(PError::unexpected(i, PREFIX_TOKENS)));
     };
     match token.kind {
         LParen => {
             let (i, right) = pratt_parse(i.slice(1..), MIN_PRECEDENCE)?;
             // next token should be RParen
-            let Some(next_token) = i.get(0) else {
-                return Err("Expect ')' token".to_string());
-            };
-            // eat RParen
-            let i = i.slice(1..);
-            match next_token.kind {
-                TokenKind::RParen => Ok((i, right)),
-                _ => Err("Expect ')' token".to_string()),
-            }
+            let (i, _) = match_token(RParen)(i)?;
+            Ok((i, right))
+        }
+        LiteralInteger | LiteralFloat | QuotedString | TRUE | FALSE | NULL => {
+            let (i, expr) = literal(i)?;
+            Ok((i, PrattExpr::Expr(expr)))
         }
-        LiteralInteger => Ok((
-            i.slice(1..),
-            PrattExpr::Expr(Expr::Literal(Literal::UnsignedInteger(
-                token.text().parse::<usize>().unwrap(),
-            ))),
-        )),
         Ident => {
-            let Ok((i, expr)) = alt((function_expr, column_ref_expr))(i) else {
-                return Err("can not find prefix expr".to_string());
-            };
+            let (i, expr) = alt((cast_expr, interval_expr, function_expr, column_ref_expr))(i)?;
             Ok((i, PrattExpr::Expr(expr)))
         }
-        NOT => {
-            let Ok((i, expr)) = exists_expr(i) else {
-                return Err("can not find prefix expr".to_string());
-            };
+        CASE => {
+            let (i, expr) = case_expr(i)?;
             Ok((i, PrattExpr::Expr(expr)))
         }
-        EXISTS => {
-            let Ok((i, expr)) = exists_expr(i) else {
-                return Err("can not find prefix expr".to_string());
-            };
+        CAST => {
+            let (i, expr) = cast_expr(i)?;
+            Ok((i, PrattExpr::Expr(expr)))
+        }
+        NOT if !matches!(i.tokens.get(1), Some(token) if token.kind == EXISTS) => {
+            let (rest, pratt_expr) = pratt_parse(i.slice(1..), prefix_precedence(PrattOp::Not))?;
+            Ok((
+                rest,
+                PrattExpr::Expr(Expr::UnaryOp {
+                    op: UnaryOp::Not,
+                    expr: Box::new(pratt_expr.into_expr()),
+                    span: consumed_span(i, rest),
+                }),
+            ))
+        }
+        NOT | EXISTS => {
+            let (i, expr) = exists_expr(i)?;
             Ok((i, PrattExpr::Expr(expr)))
         }
         Plus => {
-            let (i, pratt_expr) =
-                pratt_parse(i.slice(1..), precedence(PrattOp::Plus, AffixKind::Prefix)?)?;
+            let (rest, pratt_expr) = pratt_parse(i.slice(1..), prefix_precedence(PrattOp::Plus))?;
             Ok((
-                i,
+                rest,
                 PrattExpr::Expr(Expr::UnaryOp {
                     op: UnaryOp::Plus,
                     expr: Box::new(pratt_expr.into_expr()),
+                    span: consumed_span(i, rest),
                 }),
             ))
         }
         Minus => {
-            let (i, pratt_expr) =
-                pratt_parse(i.slice(1..), precedence(PrattOp::Minus, AffixKind::Prefix)?)?;
+            let (rest, pratt_expr) = pratt_parse(i.slice(1..), prefix_precedence(PrattOp::Minus))?;
             Ok((
-                i,
+                rest,
                 PrattExpr::Expr(Expr::UnaryOp {
                     op: UnaryOp::Minus,
                     expr: Box::new(pratt_expr.into_expr()),
+                    span: consumed_span(i, rest),
+                }),
+            ))
+        }
+        Tilde => {
+            let (rest, pratt_expr) =
+                pratt_parse(i.slice(1..), prefix_precedence(PrattOp::BitwiseNot))?;
+            Ok((
+                rest,
+                PrattExpr::Expr(Expr::UnaryOp {
+                    op: UnaryOp::BitwiseNot,
+                    expr: Box::new(pratt_expr.into_expr()),
+                    span: consumed_span(i, rest),
                 }),
             ))
         }
         SELECT => {
-            let Ok((i, stmt)) = select_stmt(i) else {
-                return Err("can not parse select statement".to_string());
-            };
-            Ok((i, PrattExpr::Expr(Expr::Subquery(Box::new(stmt)))))
+            let (rest, stmt) = select_stmt(i)?;
+            Ok((
+                rest,
+                PrattExpr::Expr(Expr::Subquery {
+                    subquery: Box::new(stmt),
+                    span: consumed_span(i, rest),
+                }),
+            ))
+        }
+        _ => Err(nom::Err::Error(PError::unexpected(i, PREFIX_TOKENS))),
+    }
+}
+
+/// Tokens that can start an expression
+const PREFIX_TOKENS: [TokenKind; 16] = [
+    LParen,
+    LiteralInteger,
+    LiteralFloat,
+    QuotedString,
+    TRUE,
+    FALSE,
+    NULL,
+    Ident,
+    CASE,
+    CAST,
+    NOT,
+    EXISTS,
+    Plus,
+    Minus,
+    Tilde,
+    SELECT,
+];
+
+fn literal(i: Input) -> IResult<Expr> {
+    let Some(token) = i.tokens.first() else {
+        return Err(nom::Err::Error(PError::unexpected(i, PREFIX_TOKENS)));
+    };
+    let literal = match token.kind {
+        LiteralInteger | LiteralFloat => Literal::Number(Number::new(token.text())),
+        // double quotes and backticks quote identifiers
+        QuotedString if token.text().starts_with('\'') => {
+            Literal::String(unescape_string(token.text()))
+        }
+        TRUE => Literal::Boolean(true),
+        FALSE => Literal::Boolean(false),
+        NULL => Literal::Null,
+        _ => return Err(nom::Err::Error(PError::unexpected(i, PREFIX_TOKENS))),
+    };
+    Ok((
+        i.slice(1..),
+        Expr::Literal {
+            literal,
+            span: token.span.clone().into(),
+        },
+    ))
+}
+
+/// A single quoted string literal, like the escape character of `LIKE`
+fn string_literal(i: Input) -> IResult<String> {
+    match i.tokens.first() {
+        Some(token) if token.kind == QuotedString && token.text().starts_with('\'') => {
+            Ok((i.slice(1..), unescape_string(token.text())))
         }
-        _ => Err("First token can't be treated as prefix".to_string()),
+        _ => Err(nom::Err::Error(PError::unexpected(i, [QuotedString]))),
+    }
+}
+
+/// The string after `ESCAPE`, empty or a single character. The `ESCAPE` before
+/// it commits to a pattern escape, so a failure is not backtracked.
+fn escape_string(i: Input) -> IResult<String> {
+    let (rest, escape) =
+        string_literal(i).map_err(|_| nom::Err::Failure(PError::unexpected(i, [QuotedString])))?;
+    if escape.chars().count() > 1 {
+        return Err(nom::Err::Failure(PError::message(
+            i,
+            "escape string should be empty or a single character",
+        )));
     }
+    Ok((rest, escape))
 }
 
-fn infix(i: Input, pratt_left: PrattExpr) -> Result<(Input, PrattExpr), String> {
-    let (i, op) = pratt_operator(i).map_err(|e| e.to_string())?;
+/// Decodes a single quoted string, where a quote is escaped as `''` or `\'`.
+fn unescape_string(text: &str) -> String {
+    let mut value = String::with_capacity(text.len());
+    let mut chars = text[1..text.len() - 1].chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                // the lexer only accepts doubled quotes inside the string
+                chars.next();
+                value.push('\'');
+            }
+            '\\' => match chars.next() {
+                Some('n') => value.push('\n'),
+                Some('r') => value.push('\r'),
+                Some('t') => value.push('\t'),
+                Some('0') => value.push('\0'),
+                Some('b') => value.push('\u{8}'),
+                Some(c) => value.push(c),
+                None => {}
+            },
+            c => value.push(c),
+        }
+    }
+    value
+}
+
+/// `start` is where the left operand begins, so the span of the result covers both operands.
+fn infix<'a>(start: Input<'a>, i: Input<'a>, pratt_left: PrattExpr) -> IResult<'a, PrattExpr> {
+    let (i, op) = pratt_operator(i)?;
     match op {
         PrattOp::Plus
         | PrattOp::Minus
         | PrattOp::Multiply
         | PrattOp::Divide
+        | PrattOp::IntDiv
+        | PrattOp::Modulo
+        | PrattOp::Exponent
+        | PrattOp::Caret
+        | PrattOp::StringConcat
+        | PrattOp::BitwiseAnd
+        | PrattOp::BitwiseOr
+        | PrattOp::ShiftLeft
+        | PrattOp::ShiftRight
         | PrattOp::Gt
         | PrattOp::Lt
         | PrattOp::GtEq
@@ -142,48 +259,186 @@ fn infix(i: Input, pratt_left: PrattExpr) -> Result<(Input, PrattExpr), String>
         | PrattOp::NotEq
         | PrattOp::And
         | PrattOp::Or => {
-            let (i, pratt_right) = pratt_parse(i, precedence(op, AffixKind::Infix)?)?;
+            let bp = precedence(op, AffixKind::Infix).expect("binary operator is infix");
+            let (i, pratt_right) = pratt_parse(i, bp)?;
             Ok((
                 i,
                 PrattExpr::Expr(Expr::BinaryOp {
                     left: Box::new(pratt_left.into_expr()),
                     op: op.to_binary_operator(),
                     right: Box::new(pratt_right.into_expr()),
+                    span: consumed_span(start, i),
                 }),
             ))
         }
+        PrattOp::BitwiseNot | PrattOp::Not => unreachable!("{op:?} is only a prefix operator"),
         // postfix
+        PrattOp::Is => {
+            let left = Box::new(pratt_left.into_expr());
+            let (i, not) = opt(match_token(NOT))(i)?;
+            let not = not.is_some();
+            if let Ok((i, _)) = tuple((match_token(DISTINCT), match_token(FROM)))(i) {
+                let bp = precedence(op, AffixKind::Infix).expect("IS is infix");
+                let (i, pratt_right) = pratt_parse(i, bp)?;
+                return Ok((
+                    i,
+                    PrattExpr::Expr(Expr::IsDistinctFrom {
+                        not,
+                        left,
+                        right: Box::new(pratt_right.into_expr()),
+                        span: consumed_span(start, i),
+                    }),
+                ));
+            }
+            let (i, token) = alt((
+                match_token(NULL),
+                match_token(TRUE),
+                match_token(FALSE),
+                match_word("UNKNOWN"),
+            ))(i)?;
+            let expr = left;
+            let span = consumed_span(start, i);
+            let expr = match token.kind {
+                NULL => Expr::IsNull { not, expr, span },
+                TRUE => Expr::IsTrue { not, expr, span },
+                FALSE => Expr::IsFalse { not, expr, span },
+                _ => Expr::IsUnknown { not, expr, span },
+            };
+            Ok((i, PrattExpr::Expr(expr)))
+        }
+        PrattOp::DoubleColon => {
+            let (i, data_type) = data_type(i)?;
+            Ok((
+                i,
+                PrattExpr::Expr(Expr::Cast {
+                    kind: CastKind::DoubleColon,
+                    expr: Box::new(pratt_left.into_expr()),
+                    data_type,
+                    span: consumed_span(start, i),
+                }),
+            ))
+        }
+        PrattOp::Between { not } => {
+            let expr = Box::new(pratt_left.into_expr());
+            // the bounds bind tighter than `AND`, so its first occurrence separates them
+            let bp = precedence(op, AffixKind::Infix).expect("BETWEEN is infix");
+            let bounds = |i| {
+                tuple((
+                    |i| pratt_parse(i, bp),
+                    match_token(AND),
+                    |i| pratt_parse(i, bp),
+                ))(i)
+            };
+            // `SYMMETRIC` is not reserved, in `a BETWEEN symmetric AND b` it is the low bound
+            let (i, (symmetric, (low, _, high))) = alt((
+                tuple((match_word("SYMMETRIC").map(|_| true), bounds)),
+                tuple((success(false), bounds)),
+            ))(i)?;
+            Ok((
+                i,
+                PrattExpr::Expr(Expr::Between {
+                    not,
+                    symmetric,
+                    expr,
+                    low: Box::new(low.into_expr()),
+                    high: Box::new(high.into_expr()),
+                    span: consumed_span(start, i),
+                }),
+            ))
+        }
+        PrattOp::Like { not }
+        | PrattOp::ILike { not }
+        | PrattOp::SimilarTo { not }
+        | PrattOp::RLike { not, .. } => {
+            let expr = Box::new(pratt_left.into_expr());
+            let bp = precedence(op, AffixKind::Infix).expect("pattern matching is infix");
+            let (i, pattern) = alt((
+                tuple((
+                    alt((
+                        match_word("ANY").map(|_| true),
+                        match_word("ALL").map(|_| false),
+                    )),
+                    match_token(LParen),
+                    comma_separated_list1(self::expr),
+                    match_token(RParen),
+                ))
+                .map(|(any, _, patterns, _)| match any {
+                    true => Pattern::Any(patterns),
+                    false => Pattern::All(patterns),
+                }),
+                |i| pratt_parse(i, bp).map(|(i, p)| (i, Pattern::Expr(Box::new(p.into_expr())))),
+            ))(i)?;
+            let (i, escape) = match opt(match_word("ESCAPE"))(i)? {
+                (i, Some(_)) => {
+                    let (rest, escape) = escape_string(i)?;
+                    (rest, Some(escape))
+                }
+                (i, None) => (i, None),
+            };
+            let span = consumed_span(start, i);
+            let expr = match op {
+                PrattOp::Like { .. } => Expr::Like {
+                    not,
+                    expr,
+                    pattern,
+                    escape,
+                    span,
+                },
+                PrattOp::ILike { .. } => Expr::ILike {
+                    not,
+                    expr,
+                    pattern,
+                    escape,
+                    span,
+                },
+                PrattOp::SimilarTo { .. } => Expr::SimilarTo {
+                    not,
+                    expr,
+                    pattern,
+                    escape,
+                    span,
+                },
+                PrattOp::RLike { regexp, .. } => Expr::RLike {
+                    regexp,
+                    not,
+                    expr,
+                    pattern,
+                    escape,
+                    span,
+                },
+                _ => unreachable!(),
+            };
+            Ok((i, PrattExpr::Expr(expr)))
+        }
         PrattOp::In { not } => {
-            if let Ok((i, subquery)) =
+            let left = Box::new(pratt_left.into_expr());
+            if let Ok((i, (_, subquery, _))) =
                 tuple((match_token(LParen), select_stmt, match_token(RParen)))(i)
-                    .map(|(i, (_, query, _))| (i, query))
             {
-                Ok((
+                return Ok((
                     i,
                     PrattExpr::Expr(Expr::InSubquery {
                         not,
-                        expr: Box::new(pratt_left.into_expr()),
+                        expr: left,
                         subquery: Box::new(subquery),
+                        span: consumed_span(start, i),
                     }),
-                ))
-            } else if let Ok((i, exprs)) = tuple((
+                ));
+            }
+            let (i, (_, list, _)) = tuple((
                 match_token(LParen),
                 comma_separated_list0(expr),
                 match_token(RParen),
-            ))(i)
-            .map(|(i, (_, exprs, _))| (i, exprs))
-            {
-                Ok((
-                    i,
-                    PrattExpr::Expr(Expr::InList {
-                        not,
-                        expr: Box::new(pratt_left.into_expr()),
-                        list: exprs,
-                    }),
-                ))
-            } else {
-                return Err(format!("Failed to parse InSubquery or InList"));
-            }
+            ))(i)?;
+            Ok((
+                i,
+                PrattExpr::Expr(Expr::InList {
+                    not,
+                    expr: left,
+                    list,
+                    span: consumed_span(start, i),
+                }),
+            ))
         }
     }
 }
@@ -206,9 +461,20 @@ enum PrattOp {
     // + -
     Plus,
     Minus,
-    // * /
+    // * / // % ** ^
     Multiply,
     Divide,
+    IntDiv,
+    Modulo,
+    Exponent,
+    Caret,
+    // || & | << >> ~
+    StringConcat,
+    BitwiseAnd,
+    BitwiseOr,
+    ShiftLeft,
+    ShiftRight,
+    BitwiseNot,
     // > < >= <= = != <>
     Gt,
     Lt,
@@ -216,19 +482,40 @@ enum PrattOp {
     LtEq,
     Eq,
     NotEq,
-    // and or
+    // and or not
     And,
     Or,
+    Not,
+    // is
+    Is,
+    // ::
+    DoubleColon,
+    // (not) between
+    Between { not: bool },
+    // (not) like, ilike, similar to, regexp, rlike
+    Like { not: bool },
+    ILike { not: bool },
+    SimilarTo { not: bool },
+    RLike { not: bool, regexp: bool },
     // (not) in
     In { not: bool },
 }
 impl PrattOp {
-    pub fn to_binary_operator(&self) -> BinaryOp {
+    pub fn to_binary_operator(self) -> BinaryOp {
         match self {
             PrattOp::Plus => BinaryOp::Add,
             PrattOp::Minus => BinaryOp::Sub,
             PrattOp::Multiply => BinaryOp::Mul,
             PrattOp::Divide => BinaryOp::Div,
+            PrattOp::IntDiv => BinaryOp::IntDiv,
+            PrattOp::Modulo => BinaryOp::Mod,
+            PrattOp::Exponent => BinaryOp::Exponent,
+            PrattOp::Caret => BinaryOp::Caret,
+            PrattOp::StringConcat => BinaryOp::StringConcat,
+            PrattOp::BitwiseAnd => BinaryOp::BitwiseAnd,
+            PrattOp::BitwiseOr => BinaryOp::BitwiseOr,
+            PrattOp::ShiftLeft => BinaryOp::ShiftLeft,
+            PrattOp::ShiftRight => BinaryOp::ShiftRight,
             PrattOp::Gt => BinaryOp::Gt,
             PrattOp::Lt => BinaryOp::Lt,
             PrattOp::GtEq => BinaryOp::GtEq,
@@ -243,11 +530,43 @@ impl PrattOp {
 }
 
 fn pratt_operator(i: Input) -> IResult<PrattOp> {
+    alt((arithmetic_operator, predicate_operator))(i)
+}
+
+/// Arithmetic, string and bitwise operators
+fn arithmetic_operator(i: Input) -> IResult<PrattOp> {
     alt((
         match_token(Plus).map(|_| PrattOp::Plus),
         match_token(Minus).map(|_| PrattOp::Minus),
         match_token(Multiply).map(|_| PrattOp::Multiply),
         match_token(Divide).map(|_| PrattOp::Divide),
+        match_token(IntDiv).map(|_| PrattOp::IntDiv),
+        match_token(Modulo).map(|_| PrattOp::Modulo),
+        match_token(DoubleStar).map(|_| PrattOp::Exponent),
+        match_token(StringConcat).map(|_| PrattOp::StringConcat),
+        match_token(BitwiseAnd).map(|_| PrattOp::BitwiseAnd),
+        match_token(BitwiseOr).map(|_| PrattOp::BitwiseOr),
+        match_token(Caret).map(|_| PrattOp::Caret),
+        match_token(ShiftLeft).map(|_| PrattOp::ShiftLeft),
+        shift_right.map(|_| PrattOp::ShiftRight),
+    ))(i)
+}
+
+/// `>>` is lexed as two adjacent `>`
+fn shift_right(i: Input) -> IResult<()> {
+    match i.tokens {
+        [first, second, ..]
+            if first.kind == Gt && second.kind == Gt && first.span.end == second.span.start =>
+        {
+            Ok((i.slice(2..), ()))
+        }
+        _ => Err(nom::Err::Error(PError::unexpected(i, [Gt]))),
+    }
+}
+
+/// Comparison, logical and the other predicate operators
+fn predicate_operator(i: Input) -> IResult<PrattOp> {
+    alt((
         match_token(Gt).map(|_| PrattOp::Gt),
         match_token(Lt).map(|_| PrattOp::Lt),
         match_token(GtEq).map(|_| PrattOp::GtEq),
@@ -256,61 +575,78 @@ fn pratt_operator(i: Input) -> IResult<PrattOp> {
         match_token(NotEq).map(|_| PrattOp::NotEq),
         match_token(AND).map(|_| PrattOp::And),
         match_token(OR).map(|_| PrattOp::Or),
+        match_token(IS).map(|_| PrattOp::Is),
+        match_token(DoubleColon).map(|_| PrattOp::DoubleColon),
         tuple((match_token(NOT), match_token(IN))).map(|_| PrattOp::In { not: true }),
         match_token(IN).map(|_| PrattOp::In { not: false }),
+        tuple((opt(match_token(NOT)), match_token(BETWEEN)))
+            .map(|(not, _)| PrattOp::Between { not: not.is_some() }),
+        pattern_operator,
     ))(i)
 }
 
-fn precedence(op: PrattOp, affix: AffixKind) -> Result<u32, String> {
+/// `[NOT] LIKE`, `[NOT] ILIKE`, `[NOT] SIMILAR TO`, `[NOT] REGEXP` and `[NOT] RLIKE`
+fn pattern_operator(i: Input) -> IResult<PrattOp> {
+    let (i, not) = opt(match_token(NOT))(i)?;
+    let not = not.is_some();
+    alt((
+        match_token(LIKE).map(move |_| PrattOp::Like { not }),
+        match_word("ILIKE").map(move |_| PrattOp::ILike { not }),
+        tuple((match_word("SIMILAR"), match_word("TO"))).map(move |_| PrattOp::SimilarTo { not }),
+        match_word("REGEXP").map(move |_| PrattOp::RLike { not, regexp: true }),
+        match_word("RLIKE").map(move |_| PrattOp::RLike { not, regexp: false }),
+    ))(i)
+}
+
+fn precedence(op: PrattOp, affix: AffixKind) -> Option<u32> {
     match affix {
-        // prefix precedence should be grater than infix
         AffixKind::Prefix => match op {
-            PrattOp::Plus | PrattOp::Minus => Ok(300),
-            _ => Err(format!("pratt op {:?} can't be treated as prefix", op)),
+            PrattOp::Not => Some(UnaryOp::Not.binding_power()),
+            PrattOp::Plus => Some(UnaryOp::Plus.binding_power()),
+            PrattOp::Minus => Some(UnaryOp::Minus.binding_power()),
+            PrattOp::BitwiseNot => Some(UnaryOp::BitwiseNot.binding_power()),
+            _ => None,
         },
         AffixKind::Infix => match op {
-            PrattOp::In { .. } => Ok(7),
-            PrattOp::Or => Ok(8),
-            PrattOp::And => Ok(9),
-            PrattOp::Gt
-            | PrattOp::Lt
-            | PrattOp::GtEq
-            | PrattOp::LtEq
-            | PrattOp::Eq
-            | PrattOp::NotEq => Ok(10),
-            PrattOp::Plus | PrattOp::Minus => Ok(11),
-            PrattOp::Multiply | PrattOp::Divide => Ok(12),
+            PrattOp::Is => Some(precedence::IS),
+            PrattOp::In { .. }
+            | PrattOp::Between { .. }
+            | PrattOp::Like { .. }
+            | PrattOp::ILike { .. }
+            | PrattOp::SimilarTo { .. }
+            | PrattOp::RLike { .. } => Some(precedence::PREDICATE),
+            PrattOp::DoubleColon => Some(precedence::DOUBLE_COLON),
+            PrattOp::BitwiseNot | PrattOp::Not => None,
+            _ => Some(op.to_binary_operator().binding_power()),
         },
     }
 }
 
+fn prefix_precedence(op: PrattOp) -> u32 {
+    precedence(op, AffixKind::Prefix).expect("operator is prefix")
+}
+
 fn column_ref_expr(i: Input) -> IResult<Expr> {
-    alt((
+    spanned(alt((
         tuple((
             ident,
             match_token(TokenKind::Dot),
-            ident,
+            qualified_ident,
             match_token(TokenKind::Dot),
-            ident,
+            qualified_ident,
         ))
-        .map(|(database, _, table, _, column)| Expr::ColumnRef {
-            database: Some(database),
-            table: Some(table),
-            column,
-        }),
-        tuple((ident, match_token(TokenKind::Dot), ident)).map(|(table, _, column)| {
-            Expr::ColumnRef {
-                database: None,
-                table: Some(table),
-                column,
-            }
-        }),
-        ident.map(|column| Expr::ColumnRef {
-            database: None,
-            table: None,
-            column,
-        }),
-    ))(i)
+        .map(|(database, _, table, _, column)| (Some(database), Some(table), column)),
+        tuple((ident, match_token(TokenKind::Dot), qualified_ident))
+            .map(|(table, _, column)| (None, Some(table), column)),
+        ident.map(|column| (None, None, column)),
+    )))
+    .map(|((database, table, column), span)| Expr::ColumnRef {
+        database,
+        table,
+        column,
+        span,
+    })
+    .parse(i)
 }
 
 fn function_expr(i: Input) -> IResult<Expr> {
@@ -319,26 +655,82 @@ fn function_expr(i: Input) -> IResult<Expr> {
         match_token(TokenKind::LParen),
         opt(match_token(TokenKind::DISTINCT)),
         comma_separated_list0(function_arg),
+        opt(null_treatment),
+        opt(tuple((
+            match_token(ORDER),
+            match_token(BY),
+            comma_separated_list1(order_by_expr),
+        ))),
+        opt(tuple((match_token(LIMIT), expr))),
         match_token(TokenKind::RParen),
+        opt(tuple((
+            match_word("WITHIN"),
+            match_token(GROUP),
+            match_token(LParen),
+            match_token(ORDER),
+            match_token(BY),
+            comma_separated_list1(order_by_expr),
+            match_token(RParen),
+        ))),
+        opt(tuple((
+            match_word("FILTER"),
+            match_token(LParen),
+            match_token(WHERE),
+            expr,
+            match_token(RParen),
+        ))),
         opt(window),
     ))(i)
-    .map(|(i, (name, _, distinct, args, _, over))| {
-        (
-            i,
-            Expr::Function {
+    .map(
+        |(
+            rest,
+            (
                 name,
-                distinct: distinct.is_some(),
+                _,
+                distinct,
                 args,
+                null_treatment,
+                order_by,
+                limit,
+                _,
+                within_group,
+                filter,
                 over,
-            },
-        )
-    })
+            ),
+        )| {
+            (
+                rest,
+                Expr::Function {
+                    name,
+                    distinct: distinct.is_some(),
+                    args,
+                    null_treatment,
+                    order_by: order_by.map_or(vec![], |o| o.2),
+                    limit: limit.map(|(_, limit)| Box::new(limit)),
+                    within_group: within_group.map_or(vec![], |w| w.5),
+                    filter: filter.map(|f| Box::new(f.3)),
+                    over: over.map(Box::new),
+                    span: consumed_span(i, rest),
+                },
+            )
+        },
+    )
+}
+
+/// `IGNORE NULLS` or `RESPECT NULLS`
+fn null_treatment(i: Input) -> IResult<NullTreatment> {
+    alt((
+        tuple((match_word("IGNORE"), match_word("NULLS"))).map(|_| NullTreatment::IgnoreNulls),
+        tuple((match_word("RESPECT"), match_word("NULLS"))).map(|_| NullTreatment::RespectNulls),
+    ))(i)
 }
 
 fn function_arg(i: Input) -> IResult<FunctionArg> {
     alt((
-        match_token(TokenKind::Multiply).map(|_| FunctionArg::Wildcard),
-        expr.map(|expr| FunctionArg::Expr(expr)),
+        match_token(TokenKind::Multiply).map(|token| FunctionArg::Wildcard {
+            span: token.span.clone().into(),
+        }),
+        expr.map(FunctionArg::Expr),
     ))(i)
 }
 
@@ -350,17 +742,78 @@ fn exists_expr(i: Input) -> IResult<Expr> {
         select_stmt,
         match_token(RParen),
     ))(i)
-    .map(|(i, (not, _, _, subquery, _))| {
+    .map(|(rest, (not, _, _, subquery, _))| {
         (
-            i,
+            rest,
             Expr::Exists {
                 not: not.is_some(),
                 subquery: Box::new(subquery),
+                span: consumed_span(i, rest),
+            },
+        )
+    })
+}
+
+/// `CAST(expr AS type)`, `TRY_CAST(expr AS type)` or `SAFE_CAST(expr AS type)`
+fn cast_expr(i: Input) -> IResult<Expr> {
+    tuple((
+        alt((
+            match_token(CAST).map(|_| CastKind::Cast),
+            // not reserved, `try_cast(a)` without `AS` is a function call
+            match_word("TRY_CAST").map(|_| CastKind::TryCast),
+            match_word("SAFE_CAST").map(|_| CastKind::SafeCast),
+        )),
+        match_token(LParen),
+        expr,
+        match_token(AS),
+        data_type,
+        match_token(RParen),
+    ))(i)
+    .map(|(rest, (kind, _, expr, _, data_type, _))| {
+        (
+            rest,
+            Expr::Cast {
+                kind,
+                expr: Box::new(expr),
+                data_type,
+                span: consumed_span(i, rest),
+            },
+        )
+    })
+}
+
+/// `CASE [operand] WHEN .. THEN .. [WHEN ..] [ELSE ..] END`
+fn case_expr(i: Input) -> IResult<Expr> {
+    tuple((
+        match_token(CASE),
+        opt(expr),
+        many1(case_when),
+        opt(tuple((match_token(ELSE), expr))),
+        match_token(END),
+    ))(i)
+    .map(|(rest, (_, operand, whens, else_result, _))| {
+        (
+            rest,
+            Expr::Case {
+                operand: operand.map(Box::new),
+                whens,
+                else_result: else_result.map(|(_, expr)| Box::new(expr)),
+                span: consumed_span(i, rest),
             },
         )
     })
 }
 
+fn case_when(i: Input) -> IResult<CaseWhen> {
+    spanned(tuple((match_token(WHEN), expr, match_token(THEN), expr)))
+        .map(|((_, condition, _, result), span)| CaseWhen {
+            condition,
+            result,
+            span,
+        })
+        .parse(i)
+}
+
 fn window(i: Input) -> IResult<Window> {
     alt((
         tuple((match_token(OVER), ident)).map(|(_, window_ref)| Window::WindowRef(window_ref)),
@@ -374,8 +827,11 @@ fn window(i: Input) -> IResult<Window> {
     ))(i)
 }
 
+/// `[window_name] [PARTITION BY ..] [ORDER BY ..] [frame]`
 pub fn window_spec(i: Input) -> IResult<WindowSpec> {
-    tuple((
+    spanned(tuple((
+        // the frame units are not reserved, `ROWS ..` is a frame rather than a window name
+        opt(preceded(not(window_frame), ident)),
         opt(tuple((
             match_token(PARTITION),
             match_token(BY),
@@ -386,88 +842,530 @@ pub fn window_spec(i: Input) -> IResult<WindowSpec> {
             match_token(BY),
             comma_separated_list1(order_by_expr),
         ))),
-    ))(i)
-    .map(|(i, (partition_by, order_by))| {
-        (
-            i,
-            WindowSpec {
-                partition_by: partition_by.map_or(vec![], |p| p.2),
-                order_by: order_by.map_or(vec![], |o| o.2),
-            },
-        )
+        opt(window_frame),
+    )))(i)
+    .map(
+        |(i, ((window_name, partition_by, order_by, frame), span))| {
+            (
+                i,
+                WindowSpec {
+                    window_name,
+                    partition_by: partition_by.map_or(vec![], |p| p.2),
+                    order_by: order_by.map_or(vec![], |o| o.2),
+                    frame,
+                    span,
+                },
+            )
+        },
+    )
+}
+
+/// `{ROWS | RANGE | GROUPS} {start | BETWEEN start AND end} [EXCLUDE ..]`
+fn window_frame(i: Input) -> IResult<WindowFrame> {
+    spanned(tuple((
+        alt((
+            match_word("ROWS").map(|_| WindowFrameUnits::Rows),
+            match_word("RANGE").map(|_| WindowFrameUnits::Range),
+            match_word("GROUPS").map(|_| WindowFrameUnits::Groups),
+        )),
+        alt((
+            tuple((
+                match_token(BETWEEN),
+                window_frame_bound,
+                match_token(AND),
+                window_frame_bound,
+            ))
+            .map(|(_, start, _, end)| (start, Some(end))),
+            window_frame_bound.map(|start| (start, None)),
+        )),
+        opt(preceded(match_token(EXCLUDE), window_frame_exclude)),
+    )))
+    .map(|((units, (start, end), exclude), span)| WindowFrame {
+        units,
+        start,
+        end,
+        exclude,
+        span,
     })
+    .parse(i)
+}
+
+fn window_frame_bound(i: Input) -> IResult<WindowFrameBound> {
+    alt((
+        tuple((match_word("UNBOUNDED"), match_word("PRECEDING")))
+            .map(|_| WindowFrameBound::UnboundedPreceding),
+        tuple((match_word("UNBOUNDED"), match_word("FOLLOWING")))
+            .map(|_| WindowFrameBound::UnboundedFollowing),
+        tuple((match_word("CURRENT"), match_word("ROW"))).map(|_| WindowFrameBound::CurrentRow),
+        tuple((expr, match_word("PRECEDING")))
+            .map(|(offset, _)| WindowFrameBound::Preceding(Box::new(offset))),
+        tuple((expr, match_word("FOLLOWING")))
+            .map(|(offset, _)| WindowFrameBound::Following(Box::new(offset))),
+    ))(i)
+}
+
+fn window_frame_exclude(i: Input) -> IResult<WindowFrameExclude> {
+    alt((
+        tuple((match_word("CURRENT"), match_word("ROW"))).map(|_| WindowFrameExclude::CurrentRow),
+        match_token(GROUP).map(|_| WindowFrameExclude::Group),
+        match_word("TIES").map(|_| WindowFrameExclude::Ties),
+        tuple((match_word("NO"), match_word("OTHERS"))).map(|_| WindowFrameExclude::NoOthers),
+    ))(i)
+}
+
+/// `INTERVAL '1' DAY`, the value is a string or a number
+fn interval_expr(i: Input) -> IResult<Expr> {
+    tuple((match_word("INTERVAL"), literal, opt(interval_unit)))(i).and_then(
+        |(rest, (_, value, unit))| match value {
+            Expr::Literal {
+                literal: value @ (Literal::String(_) | Literal::Number(_)),
+                ..
+            } => Ok((
+                rest,
+                Expr::Interval {
+                    value,
+                    unit,
+                    span: consumed_span(i, rest),
+                },
+            )),
+            _ => Err(nom::Err::Error(PError::unexpected(
+                i.slice(1..),
+                [QuotedString, LiteralInteger, LiteralFloat],
+            ))),
+        },
+    )
+}
+
+pub fn interval_unit(i: Input) -> IResult<IntervalUnit> {
+    alt((
+        match_word("YEAR").map(|_| IntervalUnit::Year),
+        match_word("MONTH").map(|_| IntervalUnit::Month),
+        match_word("WEEK").map(|_| IntervalUnit::Week),
+        match_word("DAY").map(|_| IntervalUnit::Day),
+        match_word("HOUR").map(|_| IntervalUnit::Hour),
+        match_word("MINUTE").map(|_| IntervalUnit::Minute),
+        match_word("SECOND").map(|_| IntervalUnit::Second),
+    ))(i)
 }
 
+#[cfg(test)]
 mod tests {
+    /// Parses the whole sql as an expression
+    fn parse_expr(sql: &str) -> super::Expr {
+        use super::*;
+        use crate::parser::error::Backtrace;
+        use crate::parser::tokenize_sql;
+
+        let backtrace = Backtrace::new();
+        let tokens = tokenize_sql(sql).unwrap();
+        let (rest, parsed) = expr(Input::new(&tokens, &backtrace)).unwrap();
+        assert_eq!(rest[0].kind, EOI, "{sql}");
+        parsed
+    }
 
     #[test]
     pub fn test_expr() {
         use super::*;
+        use crate::parser::error::Backtrace;
         use crate::parser::tokenize_sql;
 
-        let tokens = tokenize_sql("1*(2-3)+4/2 + t1.a");
-        let result = expr(&tokens).unwrap();
+        let backtrace = Backtrace::new();
+        let tokens = tokenize_sql("1*(2-3)+4/2 + t1.a").unwrap();
+        let result = expr(Input::new(&tokens, &backtrace)).unwrap();
         println!("expr: {}", result.1);
 
-        let tokens = tokenize_sql("t1.a != 1 or t1.b > 2 and c = 3");
-        let result = expr(&tokens).unwrap();
+        let tokens = tokenize_sql("t1.a != 1 or t1.b > 2 and c = 3").unwrap();
+        let result = expr(Input::new(&tokens, &backtrace)).unwrap();
         println!("expr: {}", result.1);
     }
 
     #[test]
     pub fn test_in_subquery() {
         use super::*;
+        use crate::parser::error::Backtrace;
         use crate::parser::tokenize_sql;
 
-        let tokens = tokenize_sql("t1.a in (select 1)");
-        let result = expr(&tokens).unwrap();
-        assert_eq!(result.0.len(), 0);
+        let tokens = tokenize_sql("t1.a in (select 1)").unwrap();
+        let backtrace = Backtrace::new();
+        let result = expr(Input::new(&tokens, &backtrace)).unwrap();
+        assert_eq!(result.0[0].kind, EOI);
         assert_eq!(format!("{}", result.1), "t1.a IN (SELECT 1)");
     }
 
     #[test]
     pub fn test_in_list() {
         use super::*;
+        use crate::parser::error::Backtrace;
         use crate::parser::tokenize_sql;
 
-        let tokens = tokenize_sql("t1.a in (1, 2, 3)");
-        let result = expr(&tokens).unwrap();
-        assert_eq!(result.0.len(), 0);
+        let tokens = tokenize_sql("t1.a in (1, 2, 3)").unwrap();
+        let backtrace = Backtrace::new();
+        let result = expr(Input::new(&tokens, &backtrace)).unwrap();
+        assert_eq!(result.0[0].kind, EOI);
         assert_eq!(format!("{}", result.1), "t1.a IN (1, 2, 3)");
     }
 
+    #[test]
+    pub fn test_operators() {
+        use super::*;
+        use crate::parser::error::Backtrace;
+        use crate::parser::tokenize_sql;
+
+        // `^` is kept apart from `**`, its meaning depends on the dialect
+        for (sql, expected) in [("2 ^ 3", BinaryOp::Caret), ("2 ** 3", BinaryOp::Exponent)] {
+            let parsed = parse_expr(sql);
+            let Expr::BinaryOp { op, .. } = parsed else {
+                panic!("expected a binary operator, got {parsed:?}");
+            };
+            assert_eq!(op, expected);
+        }
+
+        // `>>` needs adjacent `>`
+        let backtrace = Backtrace::new();
+        let tokens = tokenize_sql("a > > b").unwrap();
+        assert!(expr(Input::new(&tokens, &backtrace)).is_err());
+    }
+
+    #[test]
+    pub fn test_is() {
+        use super::*;
+
+        // IS binds looser than comparison and tighter than AND
+        let parsed = parse_expr("a AND b = c IS NOT NULL");
+        let Expr::BinaryOp { right, .. } = parsed else {
+            panic!("expected AND, got {parsed:?}");
+        };
+        let Expr::IsNull { not, expr, span } = *right else {
+            panic!("expected IS NOT NULL, got {right:?}");
+        };
+        assert!(not);
+        assert!(matches!(
+            *expr,
+            Expr::BinaryOp {
+                op: BinaryOp::Eq,
+                ..
+            }
+        ));
+        assert_eq!(span.range(), 6..23);
+    }
+
+    #[test]
+    pub fn test_not() {
+        use super::*;
+
+        // `NOT EXISTS` is still a single node
+        let parsed = parse_expr("NOT EXISTS (SELECT 1)");
+        assert!(matches!(parsed, Expr::Exists { not: true, .. }));
+
+        let parsed = parse_expr("NOT active");
+        let Expr::UnaryOp { op, span, .. } = parsed else {
+            panic!("expected NOT, got {parsed:?}");
+        };
+        assert_eq!(op, UnaryOp::Not);
+        assert_eq!(span.range(), 0..10);
+    }
+
+    #[test]
+    pub fn test_between() {
+        use super::*;
+        use crate::parser::error::Backtrace;
+        use crate::parser::tokenize_sql;
+
+        // the second AND is a boolean operator
+        let parsed = parse_expr("a BETWEEN 1 AND 2 AND b > 3");
+        let Expr::BinaryOp {
+            left,
+            op: BinaryOp::And,
+            right,
+            ..
+        } = parsed
+        else {
+            panic!("expected AND, got {parsed:?}");
+        };
+        let Expr::Between {
+            not,
+            low,
+            high,
+            span,
+            ..
+        } = *left
+        else {
+            panic!("expected BETWEEN, got {left:?}");
+        };
+        assert!(!not);
+        assert_eq!(low.to_string(), "1");
+        assert_eq!(high.to_string(), "2");
+        assert_eq!(span.range(), 0..17);
+        assert_eq!(right.to_string(), "b > 3");
+
+        // a column named `symmetric` as the low bound
+        let parsed = parse_expr("a between symmetric and 1");
+        let Expr::Between { symmetric, low, .. } = parsed else {
+            panic!("expected BETWEEN, got {parsed:?}");
+        };
+        assert!(!symmetric);
+        assert!(matches!(*low, Expr::ColumnRef { .. }));
+
+        // a missing upper bound is an error
+        let backtrace = Backtrace::new();
+        let tokens = tokenize_sql("a BETWEEN 1").unwrap();
+        assert!(expr(Input::new(&tokens, &backtrace)).is_err());
+    }
+
+    #[test]
+    pub fn test_cast() {
+        use super::*;
+        use crate::ast::data_type::DataType;
+
+        // `::` binds tighter than unary minus
+        let parsed = parse_expr("-1::text");
+        let Expr::UnaryOp { expr: operand, .. } = parsed else {
+            panic!("expected unary minus, got {parsed:?}");
+        };
+        let Expr::Cast {
+            kind,
+            data_type,
+            span,
+            ..
+        } = *operand
+        else {
+            panic!("expected cast, got {operand:?}");
+        };
+        assert_eq!(kind, CastKind::DoubleColon);
+        assert_eq!(data_type, DataType::Text);
+        assert_eq!(span.range(), 1..8);
+
+        let parsed = parse_expr("safe_cast(a AS string)");
+        assert!(matches!(
+            parsed,
+            Expr::Cast {
+                kind: CastKind::SafeCast,
+                data_type: DataType::String,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    pub fn test_case() {
+        use super::*;
+        use crate::parser::error::Backtrace;
+        use crate::parser::tokenize_sql;
+
+        let parsed = parse_expr("CASE x WHEN 1 THEN 2 WHEN 3 THEN 4 END");
+        let Expr::Case {
+            operand,
+            whens,
+            else_result,
+            span,
+        } = parsed
+        else {
+            panic!("expected CASE, got {parsed:?}");
+        };
+        assert_eq!(operand.unwrap().to_string(), "x");
+        assert_eq!(whens.len(), 2);
+        assert_eq!(whens[1].span.range(), 21..34);
+        assert!(else_result.is_none());
+        assert_eq!(span.range(), 0..38);
+
+        // at least one WHEN and a closing END
+        for sql in ["CASE x END", "CASE WHEN a THEN b"] {
+            let backtrace = Backtrace::new();
+            let tokens = tokenize_sql(sql).unwrap();
+            assert!(expr(Input::new(&tokens, &backtrace)).is_err(), "{sql}");
+        }
+    }
+
+    #[test]
+    pub fn test_pattern_matching() {
+        use super::*;
+        use crate::parser::error::Backtrace;
+        use crate::parser::tokenize_sql;
+
+        // LIKE binds tighter than comparison and looser than arithmetic
+        let parsed = parse_expr("a = b + 1 LIKE c ESCAPE '!'");
+        let Expr::BinaryOp { right, .. } = parsed else {
+            panic!("expected =, got {parsed:?}");
+        };
+        let Expr::Like {
+            not,
+            expr: left,
+            pattern,
+            escape,
+            span,
+        } = *right
+        else {
+            panic!("expected LIKE, got {right:?}");
+        };
+        assert!(!not);
+        assert!(matches!(
+            *left,
+            Expr::BinaryOp {
+                op: BinaryOp::Add,
+                ..
+            }
+        ));
+        assert!(matches!(pattern, Pattern::Expr(_)));
+        assert_eq!(escape.as_deref(), Some("!"));
+        assert_eq!(span.range(), 4..27);
+
+        let parsed = parse_expr("name NOT LIKE ALL ('a%', b)");
+        let Expr::Like { not, pattern, .. } = parsed else {
+            panic!("expected LIKE, got {parsed:?}");
+        };
+        assert!(not);
+        assert!(matches!(pattern, Pattern::All(patterns) if patterns.len() == 2));
+
+        // the escape must be a string of at most one character
+        let cases = [
+            (
+                "a LIKE b ESCAPE c",
+                "unexpected identifier, expected string",
+            ),
+            ("a LIKE b ESCAPE 1", "unexpected integer, expected string"),
+            (
+                "a NOT ILIKE b ESCAPE 'ab'",
+                "escape string should be empty or a single character",
+            ),
+        ];
+        for (sql, message) in cases {
+            let backtrace = Backtrace::new();
+            let tokens = tokenize_sql(sql).unwrap();
+            let error = expr(Input::new(&tokens, &backtrace)).unwrap_err();
+            assert!(matches!(error, nom::Err::Failure(_)), "{sql}");
+            assert_eq!(backtrace.report(error).to_string(), message);
+        }
+
+        // an error tracked further than the escape string is still reported
+        let backtrace = Backtrace::new();
+        let tokens = tokenize_sql("a LIKE b ESCAPE c d").unwrap();
+        PError::unexpected(Input::new(&tokens[5..], &backtrace), [Comma]);
+        let error = expr(Input::new(&tokens, &backtrace)).unwrap_err();
+        assert_eq!(backtrace.report(error).span, tokens[5].span);
+    }
+
+    #[test]
+    pub fn test_literal() {
+        use super::*;
+        use crate::parser::error::Backtrace;
+        use crate::parser::tokenize_sql;
+
+        let cases = [
+            ("'bob'", Literal::String("bob".to_string())),
+            ("'it''s'", Literal::String("it's".to_string())),
+            (
+                r"'a\'b\\c\nd\te\x'",
+                Literal::String("a'b\\c\nd\tex".to_string()),
+            ),
+            ("1.50", Literal::Number(Number::new("1.50"))),
+            (".5", Literal::Number(Number::new(".5"))),
+            ("0xFF_FF", Literal::Number(Number::new("0xFF_FF"))),
+            ("true", Literal::Boolean(true)),
+            ("FALSE", Literal::Boolean(false)),
+            ("null", Literal::Null),
+        ];
+        for (sql, literal) in cases {
+            assert_eq!(
+                parse_expr(sql),
+                Expr::Literal {
+                    literal,
+                    span: Default::default()
+                }
+            );
+        }
+
+        // quoted identifiers are not strings
+        let tokens = tokenize_sql("\"a\"").unwrap();
+        let backtrace = Backtrace::new();
+        assert!(expr(Input::new(&tokens, &backtrace)).is_err());
+    }
+
+    #[test]
+    pub fn test_number() {
+        use super::*;
+        use crate::ast::expr::Decimal;
+        use crate::parser::error::Backtrace;
+        use crate::parser::tokenize_sql;
+
+        let number = Number::new("99999999999999999999999");
+        assert_eq!(number.as_u64(), None);
+        assert_eq!(number.to_decimal().unwrap().to_string(), number.as_str());
+        assert_eq!(number.as_f64(), Some(1e23));
+
+        let number = Number::new("0.1");
+        assert!(!number.is_integer());
+        assert_eq!(
+            number.to_decimal(),
+            Some(Decimal {
+                digits: "1".to_string(),
+                scale: 1
+            })
+        );
+        assert_eq!(number.as_u64(), None);
+        assert_eq!(number.as_f64(), Some(0.1));
+
+        let cases = [
+            ("42", Some(42), "42"),
+            ("1_000", Some(1000), "1000"),
+            ("0xFFFF_FFFF_FFFF_FFFF", None, "18446744073709551615"),
+            (
+                "0x7fff_ffff_ffff_ffff",
+                Some(i64::MAX),
+                "9223372036854775807",
+            ),
+            ("0o_1_755", Some(1005), "1005"),
+            ("0b1010", Some(10), "10"),
+            ("1.5e3", Some(1500), "1500"),
+            ("1.500", None, "1.500"),
+            ("2.5E-3", None, "0.0025"),
+            ("00.0", Some(0), "0.0"),
+        ];
+        for (text, int, decimal) in cases {
+            let number = Number::new(text);
+            assert_eq!(number.as_i64(), int, "{text}");
+            assert_eq!(number.to_decimal().unwrap().to_string(), decimal, "{text}");
+        }
+        assert_eq!(
+            Number::new("0xFFFF_FFFF_FFFF_FFFF").as_u64(),
+            Some(u64::MAX)
+        );
+        assert_eq!(Number::new("1.500").as_u64(), None);
+        assert_eq!(Number::new("1.000").as_u64(), Some(1));
+        assert_eq!(Number::new("0b11").as_f64(), Some(3.0));
+
+        // a large integer no longer panics
+        let backtrace = Backtrace::new();
+        let tokens = tokenize_sql("99999999999999999999999").unwrap();
+        let (_, parsed) = expr(Input::new(&tokens, &backtrace)).unwrap();
+        assert_eq!(parsed.to_string(), "99999999999999999999999");
+    }
+
     #[test]
     pub fn test_column_ref() {
         use super::*;
         use crate::ast::Ident;
+        use crate::parser::error::Backtrace;
         use crate::parser::expr::column_ref_expr;
         use crate::parser::tokenize_sql;
 
-        let tokens = tokenize_sql("t1.b");
-        let column_ref = column_ref_expr(&tokens);
+        let tokens = tokenize_sql("t1.b").unwrap();
+        let backtrace = Backtrace::new();
+        let column_ref = column_ref_expr(Input::new(&tokens, &backtrace));
         println!("{:?}", column_ref);
         assert!(column_ref.is_ok());
         let column_ref = column_ref.unwrap();
-        assert_eq!(column_ref.0, vec![]);
+        assert_eq!(column_ref.0[0].kind, EOI);
         match column_ref.1 {
             Expr::ColumnRef {
                 database,
                 table,
                 column,
+                span,
             } => {
                 assert_eq!(database, None);
-                assert_eq!(
-                    table,
-                    Some(Ident {
-                        value: "t1".to_string()
-                    })
-                );
-                assert_eq!(
-                    column,
-                    Ident {
-                        value: "b".to_string()
-                    }
-                );
+                assert_eq!(table, Some(Ident::new("t1")));
+                assert_eq!(column, Ident::new("b"));
+                assert_eq!(span.range(), 0..4);
             }
             _ => panic!("should be column ref"),
         }
@@ -476,16 +1374,155 @@ mod tests {
     #[test]
     pub fn test_function() {
         use super::*;
+        use crate::parser::error::Backtrace;
         use crate::parser::expr::function_expr;
         use crate::parser::tokenize_sql;
 
-        let tokens = tokenize_sql("count(distinct a) over (partition by a order by b)");
-        let result = function_expr(&tokens);
+        let tokens = tokenize_sql("count(distinct a) over (partition by a order by b)").unwrap();
+        let backtrace = Backtrace::new();
+        let result = function_expr(Input::new(&tokens, &backtrace));
         println!("{:?}", result);
         assert!(result.is_ok());
         let result = result.unwrap();
-        assert!(result.0.is_empty());
+        assert_eq!(result.0[0].kind, EOI);
         assert!(matches!(result.1, Expr::Function { .. }));
         println!("{}", result.1);
     }
+
+    #[test]
+    pub fn test_function_clauses() {
+        use super::*;
+        use crate::parser::error::Backtrace;
+        use crate::parser::expr::function_expr;
+        use crate::parser::tokenize_sql;
+
+        let sql = "array_agg(x respect nulls order by y desc, z limit 3) \
+            within group (order by w) filter (where x > 0) over v";
+        let tokens = tokenize_sql(sql).unwrap();
+        let backtrace = Backtrace::new();
+        let (rest, function) = function_expr(Input::new(&tokens, &backtrace)).unwrap();
+        assert_eq!(rest[0].kind, EOI);
+        let Expr::Function {
+            null_treatment,
+            order_by,
+            limit,
+            within_group,
+            filter,
+            over,
+            ..
+        } = &function
+        else {
+            panic!("expected a function: {function}");
+        };
+        assert_eq!(*null_treatment, Some(NullTreatment::RespectNulls));
+        assert_eq!(order_by.len(), 2);
+        assert_eq!(limit.as_ref().unwrap().to_string(), "3");
+        assert_eq!(within_group.len(), 1);
+        assert_eq!(filter.as_ref().unwrap().to_string(), "x > 0");
+        assert!(over.is_some());
+        assert_eq!(
+            function.to_string(),
+            "array_agg(x RESPECT NULLS ORDER BY y DESC, z LIMIT 3) \
+            WITHIN GROUP (ORDER BY w) FILTER (WHERE x > 0) OVER v"
+        );
+
+        // the clauses need no arguments
+        let tokens = tokenize_sql("f(order by a)").unwrap();
+        let (_, function) = function_expr(Input::new(&tokens, &backtrace)).unwrap();
+        assert_eq!(function.to_string(), "f(ORDER BY a)");
+    }
+
+    #[test]
+    pub fn test_window_frame() {
+        use super::*;
+        use crate::ast::Ident;
+        use crate::parser::error::Backtrace;
+        use crate::parser::tokenize_sql;
+
+        let offset = |value: &str| {
+            Box::new(Expr::Literal {
+                literal: Literal::Number(Number::new(value)),
+                span: Default::default(),
+            })
+        };
+        let cases = [
+            (
+                "rows 2 preceding",
+                None,
+                WindowFrame {
+                    units: WindowFrameUnits::Rows,
+                    start: WindowFrameBound::Preceding(offset("2")),
+                    end: None,
+                    exclude: None,
+                    span: Default::default(),
+                },
+                "ROWS 2 PRECEDING",
+            ),
+            (
+                "w range between unbounded preceding and 1 following exclude current row",
+                Some("w"),
+                WindowFrame {
+                    units: WindowFrameUnits::Range,
+                    start: WindowFrameBound::UnboundedPreceding,
+                    end: Some(WindowFrameBound::Following(offset("1"))),
+                    exclude: Some(WindowFrameExclude::CurrentRow),
+                    span: Default::default(),
+                },
+                "w RANGE BETWEEN UNBOUNDED PRECEDING AND 1 FOLLOWING EXCLUDE CURRENT ROW",
+            ),
+            (
+                "groups between current row and unbounded following exclude ties",
+                None,
+                WindowFrame {
+                    units: WindowFrameUnits::Groups,
+                    start: WindowFrameBound::CurrentRow,
+                    end: Some(WindowFrameBound::UnboundedFollowing),
+                    exclude: Some(WindowFrameExclude::Ties),
+                    span: Default::default(),
+                },
+                "GROUPS BETWEEN CURRENT ROW AND UNBOUNDED FOLLOWING EXCLUDE TIES",
+            ),
+        ];
+        for (sql, window_name, frame, display) in cases {
+            let backtrace = Backtrace::new();
+            let tokens = tokenize_sql(sql).unwrap();
+            let (rest, spec) = window_spec(Input::new(&tokens, &backtrace)).unwrap();
+            assert_eq!(rest[0].kind, EOI);
+            assert_eq!(
+                spec.window_name,
+                window_name.map(|name| Ident {
+                    value: name.to_string(),
+                    span: Default::default()
+                })
+            );
+            assert_eq!(spec.frame, Some(frame));
+            assert_eq!(spec.to_string(), display);
+        }
+
+        // the frame of a `RANGE` may be an interval
+        let backtrace = Backtrace::new();
+        let tokens = tokenize_sql("order by d range interval '7' day preceding").unwrap();
+        let (rest, spec) = window_spec(Input::new(&tokens, &backtrace)).unwrap();
+        assert_eq!(rest[0].kind, EOI);
+        let Some(WindowFrame {
+            start: WindowFrameBound::Preceding(offset),
+            ..
+        }) = spec.frame
+        else {
+            panic!("expected a preceding start: {spec}");
+        };
+        assert_eq!(
+            *offset,
+            Expr::Interval {
+                value: Literal::String("7".to_string()),
+                unit: Some(IntervalUnit::Day),
+                span: Default::default(),
+            }
+        );
+
+        // a frame needs a bound
+        let tokens = tokenize_sql("rows between 1 preceding").unwrap();
+        let (rest, _) = window_spec(Input::new(&tokens, &backtrace)).unwrap();
+        assert_ne!(rest[0].kind, EOI);
+    }
 }
@@ -0,0 +1,46 @@
+use std::ops::{Deref, Range, RangeFrom};
+
+use nom::{InputLength, Slice};
+
+use super::error::Backtrace;
+use super::token::Token;
+
+/// The remaining tokens to parse, along with the state shared by the whole parse.
+#[derive(Debug, Clone, Copy)]
+pub struct Input<'a> {
+    pub tokens: &'a [Token<'a>],
+    pub backtrace: &'a Backtrace,
+}
+impl<'a> Input<'a> {
+    pub fn new(tokens: &'a [Token<'a>], backtrace: &'a Backtrace) -> Self {
+        Input { tokens, backtrace }
+    }
+
+    /// Span of the next token
+    pub fn span(&self) -> Range<usize> {
+        self.tokens.first().map_or(0..0, |token| token.span.clone())
+    }
+}
+
+impl<'a> Deref for Input<'a> {
+    type Target = [Token<'a>];
+
+    fn deref(&self) -> &Self::Target {
+        self.tokens
+    }
+}
+
+impl InputLength for Input<'_> {
+    fn input_len(&self) -> usize {
+        self.tokens.len()
+    }
+}
+
+impl Slice<RangeFrom<usize>> for Input<'_> {
+    fn slice(&self, range: RangeFrom<usize>) -> Self {
+        Input {
+            tokens: &self.tokens[range],
+            backtrace: self.backtrace,
+        }
+    }
+}
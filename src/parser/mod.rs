@@ -1,14 +1,24 @@
 pub mod common;
+pub mod data_type;
 pub mod error;
 pub mod expr;
+pub mod input;
 pub mod set_expr;
 pub mod statement;
 pub mod table_ref;
 pub mod token;
 
-pub type Input<'a> = &'a [token::Token<'a>];
+pub use self::input::Input;
+
 pub type IResult<'a, Output> = nom::IResult<Input<'a>, Output, error::PError>;
 
-pub fn tokenize_sql(sql: &str) -> Vec<token::Token> {
-    token::Tokenizer::new(sql).collect::<Vec<_>>()
+/// Tokenizes the sql, the returned tokens always end with an `EOI` token.
+pub fn tokenize_sql(sql: &str) -> Result<Vec<token::Token<'_>>, error::PError> {
+    let mut tokens = token::Tokenizer::new(sql).collect::<Result<Vec<_>, _>>()?;
+    tokens.push(token::Token {
+        source: sql,
+        kind: token::TokenKind::EOI,
+        span: sql.len()..sql.len(),
+    });
+    Ok(tokens)
 }
@@ -3,28 +3,110 @@ use nom::Parser;
 use nom::{branch::alt, sequence::tuple};
 
 use crate::ast::expr::Expr;
-use crate::ast::set_expr::{NamedWindowDef, SelectItem, SetExpr, WildcardOptions};
+use crate::ast::set_expr::{
+    NamedWindowDef, SelectItem, SetExpr, SetOperator, SetQuantifier, WildcardOptions,
+};
 use crate::parser::expr::window_spec;
+use crate::parser::statement::select_stmt;
 use crate::parser::table_ref::table_ref;
 use crate::parser::token::*;
 
-use super::common::{comma_separated_list1, ident, match_text};
+use super::common::{comma_separated_list1, consumed_span, ident, match_word, recover, spanned};
 use super::expr::expr;
 use super::{common::match_token, IResult, Input};
 
+// Tokens that a recovering parse resynchronizes at, they are also the tokens
+// allowed to follow each part of a select
+const SELECT_ITEM_SYNC: &[TokenKind] = &[
+    Comma, FROM, WHERE, GROUP, HAVING, WINDOW, UNION, INTERSECT, EXCEPT, ORDER, LIMIT, OFFSET,
+    RParen, SemiColon, EOI,
+];
+const WHERE_SYNC: &[TokenKind] = &[
+    GROUP, HAVING, WINDOW, UNION, INTERSECT, EXCEPT, ORDER, LIMIT, OFFSET, RParen, SemiColon, EOI,
+];
+const GROUP_BY_SYNC: &[TokenKind] = &[
+    Comma, HAVING, WINDOW, UNION, INTERSECT, EXCEPT, ORDER, LIMIT, OFFSET, RParen, SemiColon, EOI,
+];
+const HAVING_SYNC: &[TokenKind] = &[
+    WINDOW, UNION, INTERSECT, EXCEPT, ORDER, LIMIT, OFFSET, RParen, SemiColon, EOI,
+];
+
+/// Selects combined by set operations, `INTERSECT` binds tighter than `UNION`
+/// and `EXCEPT`, and operators of the same precedence are left associative.
+pub fn set_expr(i: Input) -> IResult<SetExpr> {
+    set_operation(i, 0)
+}
+
+fn set_operation(i: Input, lbp: u32) -> IResult<SetExpr> {
+    let start = i;
+    let (mut i, mut left) = query_term(i)?;
+    while let Ok((rest, op)) = set_operator(i) {
+        let bp = op.binding_power();
+        if lbp >= bp {
+            break;
+        }
+        let (rest, quantifier) = opt(alt((
+            match_word("ALL").map(|_| SetQuantifier::All),
+            match_token(DISTINCT).map(|_| SetQuantifier::Distinct),
+        )))(rest)?;
+        let (rest, right) = set_operation(rest, bp)?;
+        left = SetExpr::SetOperation {
+            op,
+            quantifier,
+            left: Box::new(left),
+            right: Box::new(right),
+            span: consumed_span(start, rest),
+        };
+        i = rest;
+    }
+    Ok((i, left))
+}
+
+fn set_operator(i: Input) -> IResult<SetOperator> {
+    alt((
+        match_token(UNION).map(|_| SetOperator::Union),
+        match_token(INTERSECT).map(|_| SetOperator::Intersect),
+        match_token(EXCEPT).map(|_| SetOperator::Except),
+    ))(i)
+}
+
+/// A select, a `VALUES` list or a parenthesized query
+fn query_term(i: Input) -> IResult<SetExpr> {
+    alt((
+        select_set_expr,
+        values_set_expr,
+        tuple((match_token(LParen), select_stmt, match_token(RParen))).map(|(_, query, _)| {
+            if query.with.is_none()
+                && query.order_by.is_empty()
+                && query.limit.is_none()
+                && query.offset.is_none()
+            {
+                query.body
+            } else {
+                SetExpr::Query(Box::new(query))
+            }
+        }),
+    ))(i)
+}
+
 pub fn select_set_expr(i: Input) -> IResult<SetExpr> {
-    tuple((
+    spanned(tuple((
         match_token(SELECT),
         opt(match_token(DISTINCT)),
-        comma_separated_list1(select_item),
+        comma_separated_list1(recover(select_item, SELECT_ITEM_SYNC, |span| {
+            SelectItem::UnnamedExpr(Expr::Error { span })
+        })),
         opt(tuple((match_token(FROM), table_ref))),
         opt(where_clause),
         opt(group_by_clause),
         opt(having_clause),
         opt(window_clause),
-    ))(i)
+    )))(i)
     .map(
-        |(i, (_, distinct, projection, from, selection, group_by, having, named_windows))| {
+        |(
+            i,
+            ((_, distinct, projection, from, selection, group_by, having, named_windows), span),
+        )| {
             (
                 i,
                 SetExpr::Select {
@@ -35,29 +117,52 @@ pub fn select_set_expr(i: Input) -> IResult<SetExpr> {
                     group_by: group_by.unwrap_or(vec![]),
                     having,
                     named_windows: named_windows.map_or(vec![], |v| v),
+                    span,
                 },
             )
         },
     )
 }
 
+pub fn values_set_expr(i: Input) -> IResult<SetExpr> {
+    spanned(tuple((
+        match_token(VALUES),
+        comma_separated_list1(tuple((
+            match_token(LParen),
+            comma_separated_list1(expr),
+            match_token(RParen),
+        ))),
+    )))
+    .map(|((_, rows), span)| SetExpr::Values {
+        rows: rows.into_iter().map(|(_, row, _)| row).collect(),
+        span,
+    })
+    .parse(i)
+}
+
 fn select_item(i: Input) -> IResult<SelectItem> {
     alt((
-        tuple((match_text("*"), wildcard_options))
-            .map(|(_, options)| SelectItem::Wildcard(options)),
-        tuple((expr, match_token(AS), ident))
-            .map(|(expr, _, alias)| SelectItem::ExprWithAlias { expr, alias }),
-        expr.map(|expr| SelectItem::UnnamedExpr(expr)),
+        wildcard_options.map(SelectItem::Wildcard),
+        spanned(tuple((expr, match_token(AS), ident)))
+            .map(|((expr, _, alias), span)| SelectItem::ExprWithAlias { expr, alias, span }),
+        expr.map(SelectItem::UnnamedExpr),
     ))(i)
 }
 
 fn wildcard_options(i: Input) -> IResult<WildcardOptions> {
-    tuple((
+    spanned(tuple((
+        match_token(Multiply),
         opt(tuple((
             match_token(EXCLUDE),
-            match_token(LParen),
-            comma_separated_list1(ident),
-            match_token(RParen),
+            alt((
+                tuple((
+                    match_token(LParen),
+                    comma_separated_list1(ident),
+                    match_token(RParen),
+                ))
+                .map(|(_, cols, _)| cols),
+                ident.map(|col| vec![col]),
+            )),
         ))),
         opt(tuple((
             match_token(EXCEPT),
@@ -65,29 +170,44 @@ fn wildcard_options(i: Input) -> IResult<WildcardOptions> {
             comma_separated_list1(ident),
             match_token(RParen),
         ))),
-    ))(i)
-    .map(|(i, (exclude, except))| {
-        let exclude: Vec<crate::ast::Ident> = exclude.map_or(vec![], |(_, _, cols, _)| cols);
+    )))(i)
+    .map(|(i, ((_, exclude, except), span))| {
+        let exclude: Vec<crate::ast::Ident> = exclude.map_or(vec![], |(_, cols)| cols);
         let except: Vec<crate::ast::Ident> = except.map_or(vec![], |(_, _, cols, _)| cols);
-        (i, WildcardOptions { exclude, except })
+        (
+            i,
+            WildcardOptions {
+                exclude,
+                except,
+                span,
+            },
+        )
     })
 }
 
 fn where_clause(i: Input) -> IResult<Expr> {
-    tuple((match_token(WHERE), expr))(i).map(|(i, (_, expr))| (i, expr))
+    tuple((
+        match_token(WHERE),
+        recover(expr, WHERE_SYNC, |span| Expr::Error { span }),
+    ))(i)
+    .map(|(i, (_, expr))| (i, expr))
 }
 
 fn group_by_clause(i: Input) -> IResult<Vec<Expr>> {
     tuple((
         match_token(GROUP),
         match_token(BY),
-        comma_separated_list1(expr),
+        comma_separated_list1(recover(expr, GROUP_BY_SYNC, |span| Expr::Error { span })),
     ))(i)
     .map(|(i, (_, _, group_by_list))| (i, group_by_list))
 }
 
 fn having_clause(i: Input) -> IResult<Expr> {
-    tuple((match_token(HAVING), expr))(i).map(|(i, (_, having))| (i, having))
+    tuple((
+        match_token(HAVING),
+        recover(expr, HAVING_SYNC, |span| Expr::Error { span }),
+    ))(i)
+    .map(|(i, (_, having))| (i, having))
 }
 
 fn window_clause(i: Input) -> IResult<Vec<NamedWindowDef>> {
@@ -96,14 +216,14 @@ fn window_clause(i: Input) -> IResult<Vec<NamedWindowDef>> {
 }
 
 fn named_window_def(i: Input) -> IResult<NamedWindowDef> {
-    tuple((
+    spanned(tuple((
         ident,
         match_token(AS),
         match_token(LParen),
         window_spec,
         match_token(RParen),
-    ))(i)
-    .map(|(i, (name, _, _, spec, _))| (i, NamedWindowDef { name, spec }))
+    )))(i)
+    .map(|(i, ((name, _, _, spec, _), span))| (i, NamedWindowDef { name, spec, span }))
 }
 
 #[cfg(test)]
@@ -112,10 +232,12 @@ mod tests {
     #[test]
     pub fn test_select_item_list() {
         use crate::parser::common::comma_separated_list1;
-        use crate::parser::{set_expr::select_item, tokenize_sql};
+        use crate::parser::error::Backtrace;
+        use crate::parser::{set_expr::select_item, tokenize_sql, Input};
 
-        let tokens = tokenize_sql("*, t1.a, c as d, count(e)");
-        let result = comma_separated_list1(select_item)(&tokens);
+        let tokens = tokenize_sql("*, t1.a, c as d, count(e)").unwrap();
+        let backtrace = Backtrace::new();
+        let result = comma_separated_list1(select_item)(Input::new(&tokens, &backtrace));
         println!("result: {:?}", result);
         assert!(result.is_ok());
         assert_eq!(
@@ -135,14 +257,93 @@ mod tests {
 
     #[test]
     pub fn test_select_set_expr() {
-        use crate::parser::tokenize_sql;
+        use crate::parser::error::Backtrace;
+        use crate::parser::{tokenize_sql, Input};
 
-        let tokens = tokenize_sql("select *, t1.a, c as d from t1");
-        let items = super::select_set_expr(&tokens);
+        let tokens = tokenize_sql("select *, t1.a, c as d from t1").unwrap();
+        let backtrace = Backtrace::new();
+        let items = super::select_set_expr(Input::new(&tokens, &backtrace));
         assert!(items.is_ok());
         assert_eq!(
             format!("{}", items.unwrap().1),
             "SELECT *, t1.a, c AS d FROM t1"
         );
     }
+
+    #[test]
+    pub fn test_set_expr() {
+        use crate::ast::set_expr::{SetExpr, SetOperator, SetQuantifier};
+        use crate::parser::error::Backtrace;
+        use crate::parser::token::EOI;
+        use crate::parser::{tokenize_sql, Input};
+
+        let parse = |sql| {
+            let tokens = tokenize_sql(sql).unwrap();
+            let backtrace = Backtrace::new();
+            let (rest, set_expr) = super::set_expr(Input::new(&tokens, &backtrace)).unwrap();
+            assert_eq!(rest[0].kind, EOI, "{sql}");
+            set_expr
+        };
+
+        // INTERSECT binds tighter
+        let SetExpr::SetOperation {
+            op,
+            quantifier,
+            right,
+            span,
+            ..
+        } = parse("SELECT 1 UNION ALL SELECT 2 INTERSECT SELECT 3")
+        else {
+            panic!("expected a set operation");
+        };
+        assert_eq!(op, SetOperator::Union);
+        assert_eq!(quantifier, Some(SetQuantifier::All));
+        assert!(matches!(
+            *right,
+            SetExpr::SetOperation {
+                op: SetOperator::Intersect,
+                ..
+            }
+        ));
+        assert_eq!(span.range(), 0..46);
+
+        // UNION and EXCEPT are left associative
+        let SetExpr::SetOperation { op, left, .. } =
+            parse("SELECT 1 EXCEPT SELECT 2 UNION SELECT 3")
+        else {
+            panic!("expected a set operation");
+        };
+        assert_eq!(op, SetOperator::Union);
+        assert!(matches!(
+            *left,
+            SetExpr::SetOperation {
+                op: SetOperator::Except,
+                ..
+            }
+        ));
+
+        // parentheses only group, unless the query has clauses of its own
+        assert!(matches!(parse("((SELECT 1))"), SetExpr::Select { .. }));
+        assert!(matches!(parse("(SELECT 1 LIMIT 1)"), SetExpr::Query(_)));
+
+        // `EXCEPT (` followed by columns is a wildcard option
+        let SetExpr::SetOperation { left, right, .. } =
+            parse("SELECT * EXCEPT (a) FROM t EXCEPT (SELECT * EXCEPT (a) FROM u)")
+        else {
+            panic!("expected a set operation");
+        };
+        assert_eq!(left.to_string(), "SELECT * EXCEPT (a) FROM t");
+        assert_eq!(right.to_string(), "SELECT * EXCEPT (a) FROM u");
+        assert_eq!(
+            parse("SELECT * EXCEPT (SELECT 1)").to_string(),
+            "SELECT * EXCEPT SELECT 1"
+        );
+
+        let SetExpr::Values { rows, span } = parse("values (1, 'a'), ((2), null)") else {
+            panic!("expected values");
+        };
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[1][0].to_string(), "2");
+        assert_eq!(span.range(), 0..28);
+    }
 }
@@ -3,86 +3,99 @@ use nom::combinator::opt;
 use nom::sequence::tuple;
 use nom::Parser;
 
-use crate::ast::statement::{Cte, OrderByExpr, TableAlias, With};
+use crate::ast::statement::{Cte, OrderByExpr, Statement, TableAlias, With};
 use crate::ast::{expr::Expr, statement::SelectStatement};
-use crate::parser::common::ident;
+use crate::parser::common::{ident, match_word};
 use crate::parser::token::*;
 
-use super::common::comma_separated_list1;
-use super::{common::match_token, expr::expr, set_expr::select_set_expr, IResult, Input};
+use super::common::{comma_separated_list1, spanned};
+use super::{common::match_token, expr::expr, set_expr::set_expr, IResult, Input};
+
+pub fn statement(i: Input) -> IResult<Statement> {
+    select_stmt
+        .map(|stmt| Statement::Select(Box::new(stmt)))
+        .parse(i)
+}
 
 pub fn select_stmt(i: Input) -> IResult<SelectStatement> {
-    tuple((
+    spanned(tuple((
         opt(with_clause),
-        select_set_expr,
+        set_expr,
         opt(order_by_clause),
         opt(limit_offset_clause),
-    ))(i)
-    .map(|(i, (with, select, order_by, limitoffset))| {
+    )))(i)
+    .map(|(i, ((with, body, order_by, limitoffset), span))| {
         let (limit, offset) = limitoffset.unwrap_or((None, None));
         (
             i,
             SelectStatement {
                 with,
-                body: select,
+                body,
                 order_by: order_by.unwrap_or(vec![]),
                 limit,
                 offset,
+                span,
             },
         )
     })
 }
 
 fn with_clause(i: Input) -> IResult<With> {
-    tuple((
+    spanned(tuple((
         match_token(WITH),
         opt(match_token(RECURSIVE)),
         comma_separated_list1(cte),
-    ))(i)
-    .map(|(i, (_, recursive, cte_tables))| {
+    )))(i)
+    .map(|(i, ((_, recursive, cte_tables), span))| {
         (
             i,
             With {
                 recursive: recursive.is_some(),
                 cte_tables,
+                span,
             },
         )
     })
 }
 
 fn cte(i: Input) -> IResult<Cte> {
-    tuple((
+    spanned(tuple((
         table_alias,
         match_token(AS),
         match_token(LParen),
         select_stmt,
         match_token(RParen),
-    ))(i)
-    .map(|(i, (alias, _, _, query, _))| {
+    )))(i)
+    .map(|(i, ((alias, _, _, query, _), span))| {
         (
             i,
             Cte {
                 alias,
                 query: Box::new(query),
+                span,
             },
         )
     })
 }
 
-fn table_alias(i: Input) -> IResult<TableAlias> {
-    alt((
+/// A name with optional column names, like `x (a, b)`
+pub fn table_alias(i: Input) -> IResult<TableAlias> {
+    spanned(alt((
         tuple((
             ident,
             match_token(LParen),
             comma_separated_list1(ident),
             match_token(RParen),
         ))
-        .map(|(name, _, columns, _)| TableAlias { name, columns }),
-        ident.map(|name| TableAlias {
-            name,
-            columns: vec![],
-        }),
-    ))(i)
+        .map(|(name, _, columns, _)| (name, columns)),
+        ident.map(|name| (name, vec![])),
+    )))
+    .map(|((name, columns), span)| TableAlias {
+        name,
+        columns,
+        span,
+    })
+    .parse(i)
 }
 
 fn order_by_clause(i: Input) -> IResult<Vec<OrderByExpr>> {
@@ -93,18 +106,29 @@ fn order_by_clause(i: Input) -> IResult<Vec<OrderByExpr>> {
     ))(i)
     .map(|(i, (_, _, order_by_list))| (i, order_by_list))
 }
+/// `expr [ASC | DESC] [NULLS {FIRST | LAST}]`
 pub fn order_by_expr(i: Input) -> IResult<OrderByExpr> {
-    alt((
-        tuple((expr, match_token(ASC))).map(|(expr, _)| OrderByExpr {
-            expr,
-            asc: Some(true),
-        }),
-        tuple((expr, match_token(DESC))).map(|(expr, _)| OrderByExpr {
-            expr,
-            asc: Some(false),
-        }),
-        expr.map(|expr| OrderByExpr { expr, asc: None }),
-    ))(i)
+    spanned(tuple((
+        expr,
+        opt(alt((
+            match_token(ASC).map(|_| true),
+            match_token(DESC).map(|_| false),
+        ))),
+        opt(tuple((
+            match_word("NULLS"),
+            alt((
+                match_token(FIRST).map(|_| true),
+                match_token(LAST).map(|_| false),
+            )),
+        ))),
+    )))
+    .map(|((expr, asc, nulls_first), span)| OrderByExpr {
+        expr,
+        asc,
+        nulls_first: nulls_first.map(|(_, first)| first),
+        span,
+    })
+    .parse(i)
 }
 
 fn limit_offset_clause(i: Input) -> IResult<(Option<Expr>, Option<Expr>)> {
@@ -123,7 +147,9 @@ mod tests {
     #[test]
     pub fn test_select_stmt() {
         use super::select_stmt;
-        use crate::parser::tokenize_sql;
+        use crate::parser::error::Backtrace;
+        use crate::parser::token::EOI;
+        use crate::parser::{tokenize_sql, Input};
 
         let tokens = tokenize_sql(
             "\
@@ -133,32 +159,73 @@ join t3 on t2.a = t3.a \
 left join t4 on t3.b = t4.b \
 where a > ((1 + 2) * 3) and b < c \
 group by a, c \
-having count(*) > 5\
+having count(*) > 5 \
 order by a, b desc \
 limit 1, 2",
-        );
-        let result = select_stmt(&tokens);
+        )
+        .unwrap();
+        let backtrace = Backtrace::new();
+        let result = select_stmt(Input::new(&tokens, &backtrace));
         println!("result: {:?}", result);
         assert!(result.is_ok());
         let result = result.unwrap();
-        assert_eq!(result.0, vec![]);
+        assert_eq!(result.0[0].kind, EOI);
         println!("select_stmt: {:#?}", result.1);
     }
 
     #[test]
     pub fn test_named_windows() {
         use super::select_stmt;
-        use crate::parser::tokenize_sql;
+        use crate::parser::error::Backtrace;
+        use crate::parser::token::EOI;
+        use crate::parser::{tokenize_sql, Input};
 
         let tokens = tokenize_sql(
             "\
             select count(*) over w from t window w as (partition by a order by b)",
-        );
-        let result = select_stmt(&tokens);
+        )
+        .unwrap();
+        let backtrace = Backtrace::new();
+        let result = select_stmt(Input::new(&tokens, &backtrace));
         println!("result: {:?}", result);
         assert!(result.is_ok());
         let result = result.unwrap();
-        assert_eq!(result.0, vec![]);
+        assert_eq!(result.0[0].kind, EOI);
         println!("select_stmt: {:?}", result.1);
     }
+
+    #[test]
+    pub fn test_order_by_expr() {
+        use super::order_by_expr;
+        use crate::parser::error::Backtrace;
+        use crate::parser::token::EOI;
+        use crate::parser::{tokenize_sql, Input};
+
+        let cases = [
+            ("a", None, None, "a"),
+            ("a desc", Some(false), None, "a DESC"),
+            ("a nulls first", None, Some(true), "a NULLS FIRST"),
+            (
+                "a + 1 asc nulls last",
+                Some(true),
+                Some(false),
+                "a + 1 ASC NULLS LAST",
+            ),
+        ];
+        for (sql, asc, nulls_first, display) in cases {
+            let tokens = tokenize_sql(sql).unwrap();
+            let backtrace = Backtrace::new();
+            let (rest, order_by) = order_by_expr(Input::new(&tokens, &backtrace)).unwrap();
+            assert_eq!(rest[0].kind, EOI);
+            assert_eq!(order_by.asc, asc);
+            assert_eq!(order_by.nulls_first, nulls_first);
+            assert_eq!(order_by.to_string(), display);
+        }
+
+        // `NULLS` needs a position
+        let tokens = tokenize_sql("a nulls").unwrap();
+        let backtrace = Backtrace::new();
+        let (rest, _) = order_by_expr(Input::new(&tokens, &backtrace)).unwrap();
+        assert_eq!(rest[0].text(), "nulls");
+    }
 }
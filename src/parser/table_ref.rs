@@ -4,28 +4,26 @@ use nom::sequence::tuple;
 use nom::{Parser, Slice};
 
 use super::token::*;
+use crate::ast::statement::TableAlias;
 use crate::ast::table_ref::{JoinCondition, JoinOp, TableName, TableRef};
-use crate::parser::common::{ident, match_token, AffixKind, MIN_PRECEDENCE};
+use crate::parser::common::{
+    consumed_span, ident, match_token, spanned, AffixKind, MIN_PRECEDENCE,
+};
 use crate::parser::error::PError;
 use crate::parser::expr::expr;
-use crate::parser::statement::select_stmt;
+use crate::parser::statement::{self, select_stmt};
 use crate::parser::{IResult, Input};
 
 pub fn table_ref(i: Input) -> IResult<TableRef> {
-    match pratt_parse(i, MIN_PRECEDENCE) {
-        Ok(r) => Ok(r),
-        Err(e) => Err(nom::Err::Error(PError(e))),
-    }
+    pratt_parse(i, MIN_PRECEDENCE)
 }
 
-fn pratt_parse(i: Input, lbp: u32) -> Result<(Input, TableRef), String> {
+fn pratt_parse(i: Input, lbp: u32) -> IResult<TableRef> {
+    let start = i;
     // find a prefix table_ref
     let (mut i, mut left) = prefix(i)?;
-    loop {
-        let Ok((_, op)) = pratt_operator(i) else {
-            break;
-        };
-        let Ok(bp) = precedence(op, AffixKind::Infix) else {
+    while let Ok((_, op)) = pratt_operator(i) {
+        let Some(bp) = precedence(op, AffixKind::Infix) else {
             // end of table_ref
             break;
         };
@@ -35,111 +33,101 @@ fn pratt_parse(i: Input, lbp: u32) -> Result<(Input, TableRef), String> {
             break;
         }
         // find infix table_ref with prefix table_ref
-        match infix(i, left.clone()) {
-            Ok(r) => {
-                i = r.0;
-                left = r.1
-            }
-            Err(e) => {
-                return Err(e);
-            }
-        }
+        (i, left) = infix(start, i, left)?;
     }
     Ok((i, left))
 }
 
 // find prefix table_ref
-fn prefix(i: Input) -> Result<(Input, TableRef), String> {
-    let Some(token) = i.get(0) else {
-        return Err("No token found".to_string());
+fn prefix(i: Input) -> IResult<TableRef> {
+    let Some(token) = i.tokens.first() else {
+        return Err(nom::Err::Error(PError::unexpected(
+            i,
+            [LParen, SELECT, VALUES, Ident],
+        )));
     };
     match token.kind {
         LParen => {
-            let (i, right) = pratt_parse(
-                i.slice(1..),
-                precedence(PrattOp::LParen, AffixKind::Prefix)?,
-            )?;
+            let bp = precedence(PrattOp::LParen, AffixKind::Prefix).expect("'(' is prefix");
+            let (rest, right) = pratt_parse(i.slice(1..), bp)?;
 
             // next token should be RParen
-            let Some(next_token) = i.get(0) else {
-                return Err("Expect ')' token".to_string());
-            };
-            // eat RParen
-            let i = i.slice(1..);
-            if next_token.kind != RParen {
-                return Err("Expect ')' token".to_string());
-            }
+            let (rest, _) = match_token(RParen)(rest)?;
 
             // see if there is a alias
-            match table_alias(i) {
-                Ok((i, alias)) => {
+            match table_alias(rest) {
+                Ok((alias_rest, alias)) => {
+                    let span = consumed_span(i, alias_rest);
                     let right = match right {
                         TableRef::BaseTable { name, .. } => TableRef::BaseTable {
                             name,
-                            alias: Some(alias.clone()),
+                            alias: Some(alias),
+                            span,
                         },
                         TableRef::Subquery { subquery, .. } => TableRef::Subquery {
                             subquery,
-                            alias: Some(alias.clone()),
+                            alias: Some(alias),
+                            span,
                         },
                         TableRef::Join { .. } => {
-                            return Err("Joined table should not have an alias".to_string());
+                            // forget the errors of looking for the column names of the alias
+                            i.backtrace.take();
+                            return Err(nom::Err::Error(PError::message(
+                                rest,
+                                "joined table should not have an alias",
+                            )));
                         }
                     };
-                    Ok((i, right))
+                    Ok((alias_rest, right))
                 }
-                Err(_) => Ok((i, right)),
+                Err(_) => Ok((rest, right)),
             }
         }
         // subquery
-        SELECT => {
-            let Ok((i, query)) = select_stmt(i) else {
-                return Err("can not find prefix subquery".to_string());
-            };
+        SELECT | VALUES => {
+            let (rest, query) = select_stmt(i)?;
             Ok((
-                i,
+                rest,
                 TableRef::Subquery {
                     subquery: Box::new(query),
                     alias: None,
+                    span: consumed_span(i, rest),
                 },
             ))
         }
         // base table
-        Ident => {
-            let Ok((i, table_ref)) = base_table(i) else {
-                return Err("can not find prefix base table".to_string());
-            };
-            Ok((i, table_ref))
-        }
-        _ => Err("First token can't be treated as prefix".to_string()),
+        Ident => base_table(i),
+        _ => Err(nom::Err::Error(PError::unexpected(
+            i,
+            [LParen, SELECT, VALUES, Ident],
+        ))),
     }
 }
 
-// find infix table_ref
-fn infix(i: Input, left: TableRef) -> Result<(Input, TableRef), String> {
+// find infix table_ref, `start` is where the left table_ref begins
+fn infix<'a>(start: Input<'a>, i: Input<'a>, left: TableRef) -> IResult<'a, TableRef> {
     // find infix operator to get its precedence
-    let Ok((i, op)) = pratt_operator(i) else {
-        return Err("No infix operator found".to_string());
-    };
+    let (i, op) = pratt_operator(i)?;
     match op {
         PrattOp::JoinOp(op) => {
-            let (i, right) = pratt_parse(i, precedence(PrattOp::JoinOp(op), AffixKind::Infix)?)?;
-            let Ok((i, condition)) = opt(join_condition)(i) else {
-                return Err("failed to parse join condition".to_string());
-            };
+            let bp = precedence(PrattOp::JoinOp(op), AffixKind::Infix).expect("join is infix");
+            let (i, right) = pratt_parse(i, bp)?;
+            let (i, condition) = opt(join_condition)(i)?;
             Ok((
                 i,
                 TableRef::Join {
                     op,
-                    condition: condition.map_or(JoinCondition::None, |condition| condition),
+                    condition: condition.unwrap_or(JoinCondition::None),
                     left: Box::new(left),
                     right: Box::new(right),
+                    span: consumed_span(start, i),
                 },
             ))
         }
-        _ => {
-            return Err("The pratt operator can't be treated as infix".to_string());
-        }
+        _ => Err(nom::Err::Error(PError::message(
+            i,
+            "the pratt operator can't be treated as infix",
+        ))),
     }
 }
 
@@ -152,28 +140,28 @@ fn pratt_operator(i: Input) -> IResult<PrattOp> {
     alt((
         match_token(LParen).map(|_| PrattOp::LParen),
         match_token(RParen).map(|_| PrattOp::RParen),
-        join_operator.map(|op| PrattOp::JoinOp(op)),
+        join_operator.map(PrattOp::JoinOp),
     ))(i)
 }
 
-fn precedence(op: PrattOp, affix: AffixKind) -> Result<u32, String> {
+fn precedence(op: PrattOp, affix: AffixKind) -> Option<u32> {
     match affix {
         // prefix precedence should be grater than infix
         AffixKind::Prefix => match op {
-            PrattOp::LParen => Ok(0),
-            _ => Err("pratt operator can't be treated as prefix".to_string()),
+            PrattOp::LParen => Some(0),
+            _ => None,
         },
         AffixKind::Infix => match op {
-            PrattOp::RParen => Ok(0),
-            PrattOp::JoinOp(_) => Ok(1),
-            _ => Err("pratt operator can't be treated as infix".to_string()),
+            PrattOp::RParen => Some(0),
+            PrattOp::JoinOp(_) => Some(1),
+            _ => None,
         },
     }
 }
 
 fn base_table(i: Input) -> IResult<TableRef> {
-    tuple((table_name, opt(table_alias)))(i)
-        .map(|(i, (name, alias))| (i, TableRef::BaseTable { name, alias }))
+    spanned(tuple((table_name, opt(table_alias))))(i)
+        .map(|(i, ((name, alias), span))| (i, TableRef::BaseTable { name, alias, span }))
 }
 
 fn join_condition(i: Input) -> IResult<JoinCondition> {
@@ -189,7 +177,7 @@ fn join_operator(i: Input) -> IResult<JoinOp> {
             .map(|(_, _, _)| JoinOp::LeftOuter),
         tuple((match_token(RIGHT), match_token(JOIN))).map(|(_, _)| JoinOp::RightOuter),
         tuple((match_token(RIGHT), match_token(OUTER), match_token(JOIN)))
-            .map(|(_, _, _)| JoinOp::LeftOuter),
+            .map(|(_, _, _)| JoinOp::RightOuter),
         tuple((match_token(FULL), match_token(JOIN))).map(|(_, _)| JoinOp::FullOuter),
         tuple((match_token(FULL), match_token(OUTER), match_token(JOIN)))
             .map(|(_, _, _)| JoinOp::FullOuter),
@@ -198,40 +186,48 @@ fn join_operator(i: Input) -> IResult<JoinOp> {
 }
 
 fn table_name(i: Input) -> IResult<TableName> {
-    alt((
-        tuple((ident, match_token(Dot), ident)).map(|(database, _, table)| TableName {
-            database: Some(database),
-            table,
-        }),
-        ident.map(|table| TableName {
-            database: None,
-            table,
-        }),
-    ))(i)
+    spanned(alt((
+        tuple((ident, match_token(Dot), ident)).map(|(database, _, table)| (Some(database), table)),
+        ident.map(|table| (None, table)),
+    )))
+    .map(|((database, table), span)| TableName {
+        database,
+        table,
+        span,
+    })
+    .parse(i)
 }
 
-fn table_alias(i: Input) -> IResult<crate::ast::Ident> {
-    alt((
-        tuple((match_token(AS), ident)).map(|(_, alias)| alias),
-        ident,
-    ))(i)
+fn table_alias(i: Input) -> IResult<TableAlias> {
+    tuple((opt(match_token(AS)), statement::table_alias))
+        .map(|(_, alias)| alias)
+        .parse(i)
 }
 
 #[cfg(test)]
 mod tests {
     #[test]
     pub fn test_table_ref() {
-        use crate::parser::tokenize_sql;
+        use crate::parser::error::Backtrace;
+        use crate::parser::{tokenize_sql, Input};
 
         let tokens = tokenize_sql("\
         (select * from t1) as t join t2 on t.a = t2.a left join (t3 right join t4 on t3.c = t4.c) on t2.b = t3.b\
-        ");
-        let result = super::table_ref(&tokens);
+        ").unwrap();
+        let backtrace = Backtrace::new();
+        let result = super::table_ref(Input::new(&tokens, &backtrace));
         println!("{:?}", result);
         assert!(result.is_ok());
         assert_eq!(
             format!("{}", result.unwrap().1),
-            "(((SELECT * FROM t1) AS t INNER JOIN t2 ON (t.a = t2.a)) LEFT OUTER JOIN (t3 RIGHT OUTER JOIN t4 ON (t3.c = t4.c)) ON (t2.b = t3.b))"
+            "(SELECT * FROM t1) AS t INNER JOIN t2 ON t.a = t2.a LEFT OUTER JOIN (t3 RIGHT OUTER JOIN t4 ON t3.c = t4.c) ON t2.b = t3.b"
+        );
+
+        let tokens = tokenize_sql("(values (1, 2)) v (a, b) cross join t1 as x(c)").unwrap();
+        let result = super::table_ref(Input::new(&tokens, &backtrace));
+        assert_eq!(
+            result.unwrap().1.to_string(),
+            "(VALUES (1, 2)) AS v (a, b) CROSS JOIN t1 AS x (c)"
         );
     }
 }
@@ -1,6 +1,8 @@
 use std::ops::Range;
 
-use logos::{Lexer, Logos};
+use logos::{FilterResult, Lexer, Logos};
+
+use super::error::{LexError, PError};
 
 pub use self::TokenKind::*;
 
@@ -24,6 +26,7 @@ impl std::fmt::Display for Token<'_> {
     }
 }
 
+/// Splits the source sql into tokens, lexing goes on after the span of a lexical error.
 pub struct Tokenizer<'a> {
     source: &'a str,
     lexer: Lexer<'a, TokenKind>,
@@ -35,27 +38,88 @@ impl<'a> Tokenizer<'a> {
             lexer: TokenKind::lexer(source),
         }
     }
+
+    fn token(&self, kind: TokenKind, span: Range<usize>) -> Result<Token<'a>, PError> {
+        if matches!(kind, LiteralInteger | LiteralFloat) {
+            // a number can't be directly followed by letters, digits or another dot
+            let rest = &self.source[span.end..];
+            let junk = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '.'))
+                .unwrap_or(rest.len());
+            if junk > 0 || !well_formed_number(&self.source[span.clone()]) {
+                return Err(PError::lexical(
+                    LexError::MalformedNumber,
+                    span.start..span.end + junk,
+                ));
+            }
+        }
+        Ok(Token {
+            source: self.source,
+            kind,
+            span,
+        })
+    }
+
+    fn error(&self, start: usize) -> PError {
+        let rest = &self.source[start..];
+        if rest.starts_with(['\'', '"', '`']) {
+            PError::lexical(LexError::UnterminatedQuote, start..self.source.len())
+        } else if rest.starts_with("/*") {
+            PError::lexical(LexError::UnterminatedBlockComment, start..self.source.len())
+        } else {
+            let c = rest.chars().next().unwrap_or_default();
+            PError::lexical(LexError::InvalidCharacter(c), start..start + c.len_utf8())
+        }
+    }
 }
+// The lexer doesn't backtrack, so a number token may still end in `_` or be
+// a bare radix prefix like `0x`
+fn well_formed_number(text: &str) -> bool {
+    let prefixed = text.starts_with('0') && text[1..].starts_with(['x', 'X', 'o', 'O', 'b', 'B']);
+    let hex = prefixed && text[1..].starts_with(['x', 'X']);
+    let is_digit = |c: char| {
+        if hex {
+            c.is_ascii_hexdigit()
+        } else {
+            c.is_ascii_digit()
+        }
+    };
+    let chars = text.chars().collect::<Vec<_>>();
+    let last = chars[chars.len() - 1];
+    if !(is_digit(last) || last == '.') {
+        return false;
+    }
+    chars.iter().enumerate().all(|(i, c)| {
+        // a separator goes between digits, or right after a radix prefix
+        *c != '_'
+            || i > 0 && (is_digit(chars[i - 1]) || prefixed && i == 2) && is_digit(chars[i + 1])
+    })
+}
+
 impl<'a> Iterator for Tokenizer<'a> {
-    type Item = Token<'a>;
+    type Item = Result<Token<'a>, PError>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        match self.lexer.next() {
-            Some(kind) => match kind {
-                Ok(kind) => Some(Token {
-                    source: self.source,
-                    kind,
-                    span: self.lexer.span(),
-                }),
-                Err(_) => panic!("Unable to recognize the rest tokens"),
-            },
-            None => None,
+        let kind = self.lexer.next()?;
+        let span = self.lexer.span();
+        let token = match kind {
+            Ok(kind) => self.token(kind, span.clone()),
+            Err(_) => Err(self.error(span.start)),
+        };
+        if let Err(e) = &token {
+            // continue after the erroneous text
+            self.lexer.bump(e.span.end.saturating_sub(span.end));
         }
+        Some(token)
     }
 }
 
-#[derive(Logos, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[allow(non_camel_case_types)]
+#[derive(Logos, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum TokenKind {
+    // End of input, appended by `tokenize_sql`
+    EOI,
+
     // Skip
     #[regex(r"[ \t\r\n\f]+", logos::skip)]
     Whitespace,
@@ -63,7 +127,8 @@ pub enum TokenKind {
     #[regex(r"--[^\n\f]*", logos::skip)]
     Comment,
 
-    #[regex(r"/\*[^\+]([^\*]|(\*[^/]))*\*/", logos::skip)]
+    // Also skips optimizer hints like `/*+ hint */`
+    #[token("/*", block_comment)]
     CommentBlock,
 
     #[regex(r#"[_a-zA-Z][_$a-zA-Z0-9]*"#)]
@@ -74,11 +139,17 @@ pub enum TokenKind {
     #[regex(r#"'([^'\\]|\\.|'')*'"#)]
     QuotedString,
 
-    #[regex(r"[0-9]+")]
+    // Underscores may group digits, as in `1_000_000` or `0xFFFF_FFFF`
+    #[regex(r"[0-9]+(_[0-9]+)*")]
+    #[regex(r"0[xX]_?[0-9a-fA-F]+(_[0-9a-fA-F]+)*")]
+    #[regex(r"0[oO]_?[0-7]+(_[0-7]+)*")]
+    #[regex(r"0[bB]_?[01]+(_[01]+)*")]
     LiteralInteger,
 
-    #[regex(r"[0-9]+[eE][+-]?[0-9]+")]
-    #[regex(r"([0-9]*\.[0-9]+([eE][+-]?[0-9]+)?)|([0-9]+\.[0-9]*([eE][+-]?[0-9]+)?)")]
+    #[regex(r"[0-9]+(_[0-9]+)*[eE][+-]?[0-9]+(_[0-9]+)*")]
+    #[regex(
+        r"(([0-9]+(_[0-9]+)*)?\.[0-9]+(_[0-9]+)*|[0-9]+(_[0-9]+)*\.)([eE][+-]?[0-9]+(_[0-9]+)*)?"
+    )]
     LiteralFloat,
 
     #[token("=")]
@@ -121,18 +192,46 @@ pub enum TokenKind {
     #[token("||")]
     StringConcat,
 
+    #[token("&")]
+    BitwiseAnd,
+
+    #[token("|")]
+    BitwiseOr,
+
+    #[token("^")]
+    Caret,
+
+    #[token("~")]
+    Tilde,
+
+    // `>>` is lexed as two `>`, so nested types like `ARRAY<ARRAY<INT>>` can be closed
+    #[token("<<")]
+    ShiftLeft,
+
+    #[token("**")]
+    DoubleStar,
+
     #[token("(")]
     LParen,
 
     #[token(")")]
     RParen,
 
+    #[token("[")]
+    LBracket,
+
+    #[token("]")]
+    RBracket,
+
     #[token(",")]
     Comma,
 
     #[token(".")]
     Dot,
 
+    #[token("::")]
+    DoubleColon,
+
     #[token(";")]
     SemiColon,
 
@@ -146,9 +245,18 @@ pub enum TokenKind {
     #[token("ASC", ignore(ascii_case))]
     ASC,
 
+    #[token("BETWEEN", ignore(ascii_case))]
+    BETWEEN,
+
     #[token("BY", ignore(ascii_case))]
     BY,
 
+    #[token("CASE", ignore(ascii_case))]
+    CASE,
+
+    #[token("CAST", ignore(ascii_case))]
+    CAST,
+
     #[token("CROSS", ignore(ascii_case))]
     CROSS,
 
@@ -158,6 +266,12 @@ pub enum TokenKind {
     #[token("DISTINCT", ignore(ascii_case))]
     DISTINCT,
 
+    #[token("ELSE", ignore(ascii_case))]
+    ELSE,
+
+    #[token("END", ignore(ascii_case))]
+    END,
+
     #[token("EXCEPT", ignore(ascii_case))]
     EXCEPT,
 
@@ -167,6 +281,9 @@ pub enum TokenKind {
     #[token("EXISTS", ignore(ascii_case))]
     EXISTS,
 
+    #[token("FALSE", ignore(ascii_case))]
+    FALSE,
+
     #[token("FROM", ignore(ascii_case))]
     FROM,
 
@@ -185,6 +302,9 @@ pub enum TokenKind {
     #[token("INNER", ignore(ascii_case))]
     INNER,
 
+    #[token("INTERSECT", ignore(ascii_case))]
+    INTERSECT,
+
     #[token("INTO", ignore(ascii_case))]
     INTO,
 
@@ -239,6 +359,21 @@ pub enum TokenKind {
     #[token("SELECT", ignore(ascii_case))]
     SELECT,
 
+    #[token("THEN", ignore(ascii_case))]
+    THEN,
+
+    #[token("TRUE", ignore(ascii_case))]
+    TRUE,
+
+    #[token("UNION", ignore(ascii_case))]
+    UNION,
+
+    #[token("VALUES", ignore(ascii_case))]
+    VALUES,
+
+    #[token("WHEN", ignore(ascii_case))]
+    WHEN,
+
     #[token("WHERE", ignore(ascii_case))]
     WHERE,
 
@@ -254,6 +389,17 @@ pub enum TokenKind {
     #[token("LAST", ignore(ascii_case))]
     LAST,
 }
+
+fn block_comment(lex: &mut Lexer<TokenKind>) -> FilterResult<(), ()> {
+    match lex.remainder().find("*/") {
+        Some(end) => {
+            lex.bump(end + 2);
+            FilterResult::Skip
+        }
+        None => FilterResult::Error(()),
+    }
+}
+
 impl TokenKind {
     pub fn is_literal(&self) -> bool {
         matches!(self, LiteralInteger | LiteralFloat | QuotedString)
@@ -262,7 +408,7 @@ impl TokenKind {
     pub fn is_keyword(&self) -> bool {
         !matches!(
             self,
-            Ident
+            EOI | Ident
                 | QuotedString
                 | LiteralInteger
                 | LiteralFloat
@@ -279,10 +425,19 @@ impl TokenKind {
                 | IntDiv
                 | Modulo
                 | StringConcat
+                | BitwiseAnd
+                | BitwiseOr
+                | Caret
+                | Tilde
+                | ShiftLeft
+                | DoubleStar
                 | LParen
                 | RParen
+                | LBracket
+                | RBracket
                 | Comma
                 | Dot
+                | DoubleColon
         )
     }
 }
@@ -290,6 +445,7 @@ impl TokenKind {
 impl std::fmt::Display for TokenKind {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
+            EOI => write!(f, "EOI"),
             Whitespace => write!(f, "Whitespace"),
             Comment => write!(f, "Comment"),
             CommentBlock => write!(f, "CommentBlock"),
@@ -310,27 +466,43 @@ impl std::fmt::Display for TokenKind {
             IntDiv => write!(f, "IntDiv"),
             Modulo => write!(f, "Modulo"),
             StringConcat => write!(f, "StringConcat"),
+            BitwiseAnd => write!(f, "BitwiseAnd"),
+            BitwiseOr => write!(f, "BitwiseOr"),
+            Caret => write!(f, "Caret"),
+            Tilde => write!(f, "Tilde"),
+            ShiftLeft => write!(f, "ShiftLeft"),
+            DoubleStar => write!(f, "DoubleStar"),
             LParen => write!(f, "LParen"),
             RParen => write!(f, "RParen"),
+            LBracket => write!(f, "LBracket"),
+            RBracket => write!(f, "RBracket"),
             Comma => write!(f, "Comma"),
             Dot => write!(f, "Dot"),
+            DoubleColon => write!(f, "DoubleColon"),
             SemiColon => write!(f, "SemiColon"),
             AND => write!(f, "AND"),
             AS => write!(f, "AS"),
             ASC => write!(f, "ASC"),
+            BETWEEN => write!(f, "BETWEEN"),
             BY => write!(f, "BY"),
+            CASE => write!(f, "CASE"),
+            CAST => write!(f, "CAST"),
             CROSS => write!(f, "CROSS"),
             DESC => write!(f, "DESC"),
             DISTINCT => write!(f, "DISTINCT"),
+            ELSE => write!(f, "ELSE"),
+            END => write!(f, "END"),
             EXCEPT => write!(f, "EXCEPT"),
             EXCLUDE => write!(f, "EXCLUDE"),
             EXISTS => write!(f, "EXISTS"),
+            FALSE => write!(f, "FALSE"),
             FROM => write!(f, "FROM"),
             FULL => write!(f, "FULL"),
             GROUP => write!(f, "GROUP"),
             HAVING => write!(f, "HAVING"),
             IN => write!(f, "IN"),
             INNER => write!(f, "INNER"),
+            INTERSECT => write!(f, "INTERSECT"),
             INTO => write!(f, "INTO"),
             IS => write!(f, "IS"),
             JOIN => write!(f, "JOIN"),
@@ -349,6 +521,11 @@ impl std::fmt::Display for TokenKind {
             RECURSIVE => write!(f, "RECURSIVE"),
             RIGHT => write!(f, "RIGHT"),
             SELECT => write!(f, "SELECT"),
+            THEN => write!(f, "THEN"),
+            TRUE => write!(f, "TRUE"),
+            UNION => write!(f, "UNION"),
+            VALUES => write!(f, "VALUES"),
+            WHEN => write!(f, "WHEN"),
             WHERE => write!(f, "WHERE"),
             WINDOW => write!(f, "WINDOW"),
             WITH => write!(f, "WITH"),
@@ -366,7 +543,7 @@ mod tests {
         let sql = "Select a, t1.b, count(c) FROM t1 where a > 1 order by b limit 10, 20";
         let tokenizer = Tokenizer::new(sql);
 
-        let tokens = tokenizer.collect::<Vec<_>>();
+        let tokens = tokenizer.collect::<Result<Vec<_>, _>>().unwrap();
 
         #[rustfmt::skip]
         let expected = vec![
@@ -398,4 +575,79 @@ mod tests {
 
         assert_eq!(tokens, expected);
     }
+
+    #[test]
+    pub fn test_comment() {
+        use super::*;
+        let sql = "select /* a ** comment */ a /*+ hint */ from /**/ t -- end";
+        let kinds = Tokenizer::new(sql)
+            .map(|token| token.unwrap().kind)
+            .collect::<Vec<_>>();
+        assert_eq!(kinds, vec![SELECT, Ident, FROM, Ident]);
+    }
+
+    #[test]
+    pub fn test_number() {
+        use super::*;
+        let cases = [
+            ("42", LiteralInteger),
+            ("1_000_000", LiteralInteger),
+            ("0xFFFF_FFFF", LiteralInteger),
+            ("0o_1_755", LiteralInteger),
+            ("0B1010", LiteralInteger),
+            ("99999999999999999999999", LiteralInteger),
+            ("1.618_034", LiteralFloat),
+            (".5", LiteralFloat),
+            ("1.", LiteralFloat),
+            ("1_0e1_0", LiteralFloat),
+            ("2.5E-3", LiteralFloat),
+        ];
+        for (sql, kind) in cases {
+            let tokens = Tokenizer::new(sql).collect::<Result<Vec<_>, _>>().unwrap();
+            assert_eq!(tokens.len(), 1, "{sql}");
+            assert_eq!(tokens[0].kind, kind, "{sql}");
+            assert_eq!(tokens[0].text(), sql);
+        }
+    }
+
+    #[test]
+    pub fn test_lexical_error() {
+        use super::*;
+        use crate::parser::error::{ErrorKind, LexError};
+        use crate::parser::tokenize_sql;
+
+        let cases = [
+            ("select 'abc", LexError::UnterminatedQuote, 7..11),
+            ("select `abc", LexError::UnterminatedQuote, 7..11),
+            ("select a /* b", LexError::UnterminatedBlockComment, 9..13),
+            ("select @a", LexError::InvalidCharacter('@'), 7..8),
+            ("select $1", LexError::InvalidCharacter('$'), 7..8),
+            ("select a # b", LexError::InvalidCharacter('#'), 9..10),
+            ("select 1e", LexError::MalformedNumber, 7..9),
+            ("select 1.2.3 from t", LexError::MalformedNumber, 7..12),
+            ("select 12abc", LexError::MalformedNumber, 7..12),
+            ("select 0x", LexError::MalformedNumber, 7..9),
+            ("select 0b102", LexError::MalformedNumber, 7..12),
+            ("select 1__000", LexError::MalformedNumber, 7..13),
+            ("select 1_", LexError::MalformedNumber, 7..9),
+            ("select 0x_", LexError::MalformedNumber, 7..10),
+            ("select 1e_5", LexError::MalformedNumber, 7..11),
+            ("select 1._5", LexError::MalformedNumber, 7..11),
+            ("select 1_.5", LexError::MalformedNumber, 7..11),
+        ];
+        for (sql, error, span) in cases {
+            let result = tokenize_sql(sql);
+            let e = result.unwrap_err();
+            assert_eq!(e.kind, ErrorKind::Lexical(error), "{sql}");
+            assert_eq!(e.span, span, "{sql}");
+        }
+
+        // the tokenizer goes on after an error
+        let tokens = Tokenizer::new("a @ 1x b 'c").collect::<Vec<_>>();
+        assert_eq!(tokens.len(), 5);
+        assert!(tokens[1].is_err());
+        assert!(tokens[2].is_err());
+        assert_eq!(tokens[3].as_ref().unwrap().text(), "b");
+        assert!(tokens[4].is_err());
+    }
 }
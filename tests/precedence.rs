@@ -0,0 +1,230 @@
+//! Operator precedence conformance.
+//!
+//! Each expression must parse to the same tree as its explicitly parenthesized
+//! form, written out by hand or generated for every pair of operators from the
+//! PostgreSQL operator precedence table.
+
+use sqlparser_nom::parse_query;
+
+#[test]
+pub fn test_precedence() {
+    let cases = [
+        // OR and AND
+        ("a OR b AND c", "a OR (b AND c)"),
+        ("a AND b OR c", "(a AND b) OR c"),
+        ("a OR b OR c", "(a OR b) OR c"),
+        ("a AND b AND c", "(a AND b) AND c"),
+        // NOT
+        ("NOT a AND b", "(NOT a) AND b"),
+        ("NOT a OR b", "(NOT a) OR b"),
+        ("a AND NOT b OR c", "(a AND (NOT b)) OR c"),
+        ("NOT NOT a", "NOT (NOT a)"),
+        ("NOT a IS NULL", "NOT (a IS NULL)"),
+        ("NOT a IS DISTINCT FROM b", "NOT (a IS DISTINCT FROM b)"),
+        ("NOT a = b", "NOT (a = b)"),
+        ("NOT a LIKE b", "NOT (a LIKE b)"),
+        ("NOT a IN (1, 2)", "NOT (a IN (1, 2))"),
+        ("NOT a BETWEEN b AND c", "NOT (a BETWEEN b AND c)"),
+        ("NOT a || b", "NOT (a || b)"),
+        ("NOT a + b", "NOT (a + b)"),
+        ("a = NOT b", "a = (NOT b)"),
+        // IS
+        ("a IS NULL AND b", "(a IS NULL) AND b"),
+        ("a OR b IS NULL", "a OR (b IS NULL)"),
+        ("a IS NULL IS NULL", "(a IS NULL) IS NULL"),
+        (
+            "a IS NOT DISTINCT FROM b AND c",
+            "(a IS NOT DISTINCT FROM b) AND c",
+        ),
+        (
+            "a AND b IS NOT DISTINCT FROM c",
+            "a AND (b IS NOT DISTINCT FROM c)",
+        ),
+        (
+            "a IS DISTINCT FROM b IS NULL",
+            "(a IS DISTINCT FROM b) IS NULL",
+        ),
+        ("a = b IS NULL", "(a = b) IS NULL"),
+        ("a = b IS TRUE", "(a = b) IS TRUE"),
+        ("a = b IS DISTINCT FROM c", "(a = b) IS DISTINCT FROM c"),
+        ("a IS DISTINCT FROM b = c", "a IS DISTINCT FROM (b = c)"),
+        ("a LIKE b IS NULL", "(a LIKE b) IS NULL"),
+        ("a + b IS NULL", "(a + b) IS NULL"),
+        ("a IS DISTINCT FROM b + c", "a IS DISTINCT FROM (b + c)"),
+        // comparisons, non-associative in PostgreSQL, they group to the left here
+        ("a = b AND c", "(a = b) AND c"),
+        ("a AND b = c", "a AND (b = c)"),
+        ("a = b OR c = d", "(a = b) OR (c = d)"),
+        ("a = b = c", "(a = b) = c"),
+        ("a < b <> c", "(a < b) <> c"),
+        ("a = b LIKE c", "a = (b LIKE c)"),
+        ("a LIKE b = c", "(a LIKE b) = c"),
+        ("a = b IN (1, 2)", "a = (b IN (1, 2))"),
+        ("a IN (1, 2) = b", "(a IN (1, 2)) = b"),
+        ("a = b NOT IN (SELECT 1)", "a = (b NOT IN (SELECT 1))"),
+        ("a = b BETWEEN c AND d", "a = (b BETWEEN c AND d)"),
+        ("a BETWEEN b AND c = d", "(a BETWEEN b AND c) = d"),
+        ("a = b || c", "a = (b || c)"),
+        ("a || b = c", "(a || b) = c"),
+        ("a >= b + c", "a >= (b + c)"),
+        ("a + b >= c", "(a + b) >= c"),
+        ("a = - b", "a = (- b)"),
+        // IN, BETWEEN and the pattern matching operators
+        ("a LIKE b AND c", "(a LIKE b) AND c"),
+        ("a AND b LIKE c", "a AND (b LIKE c)"),
+        ("a LIKE b LIKE c", "(a LIKE b) LIKE c"),
+        ("a SIMILAR TO b || c", "a SIMILAR TO (b || c)"),
+        ("a LIKE b || c", "a LIKE (b || c)"),
+        ("a || b LIKE c", "(a || b) LIKE c"),
+        ("a ILIKE b + c", "a ILIKE (b + c)"),
+        ("a IN (1, 2) AND b", "(a IN (1, 2)) AND b"),
+        ("a AND b IN (1, 2)", "a AND (b IN (1, 2))"),
+        ("a || b IN (1, 2)", "(a || b) IN (1, 2)"),
+        ("a + b NOT IN (1, 2)", "(a + b) NOT IN (1, 2)"),
+        ("a LIKE b IN (1, 2)", "(a LIKE b) IN (1, 2)"),
+        ("a BETWEEN b AND c AND d", "(a BETWEEN b AND c) AND d"),
+        ("a BETWEEN b AND c OR d", "(a BETWEEN b AND c) OR d"),
+        ("a OR b BETWEEN c AND d", "a OR (b BETWEEN c AND d)"),
+        (
+            "a BETWEEN b || c AND d || e",
+            "a BETWEEN (b || c) AND (d || e)",
+        ),
+        ("a BETWEEN b + c AND d * e", "a BETWEEN (b + c) AND (d * e)"),
+        (
+            "a NOT BETWEEN SYMMETRIC b AND c - d",
+            "a NOT BETWEEN SYMMETRIC b AND (c - d)",
+        ),
+        ("a || b BETWEEN c AND d", "(a || b) BETWEEN c AND d"),
+        ("a BETWEEN - b AND c::INT", "a BETWEEN (- b) AND (c::INT)"),
+        // ||, |, & and the shifts, a single level in PostgreSQL unlike in MySQL
+        ("a || b || c", "(a || b) || c"),
+        ("a || b | c", "(a || b) | c"),
+        ("a | b || c", "(a | b) || c"),
+        ("a | b | c", "(a | b) | c"),
+        ("a | b & c", "(a | b) & c"),
+        ("a & b | c", "(a & b) | c"),
+        ("a & b & c", "(a & b) & c"),
+        ("a & b << c", "(a & b) << c"),
+        ("a << b & c", "(a << b) & c"),
+        ("a << b >> c", "(a << b) >> c"),
+        ("a || b << c", "(a || b) << c"),
+        ("a >> b || c", "(a >> b) || c"),
+        ("a << b + c", "a << (b + c)"),
+        ("a + b << c", "(a + b) << c"),
+        ("a || b + c", "a || (b + c)"),
+        ("a + b || c", "(a + b) || c"),
+        ("a | b * c", "a | (b * c)"),
+        ("a * b & c", "(a * b) & c"),
+        // arithmetic
+        ("a + b - c", "(a + b) - c"),
+        ("a - b - c", "(a - b) - c"),
+        ("a + b * c", "a + (b * c)"),
+        ("a * b + c", "(a * b) + c"),
+        ("a - b / c", "a - (b / c)"),
+        ("a % b - c", "(a % b) - c"),
+        ("a / b * c", "(a / b) * c"),
+        ("a // b % c", "(a // b) % c"),
+        ("a * b ^ c", "a * (b ^ c)"),
+        ("a ^ b * c", "(a ^ b) * c"),
+        ("a + b ^ c", "a + (b ^ c)"),
+        ("a ^ b ^ c", "(a ^ b) ^ c"),
+        ("a * b ** c", "a * (b ** c)"),
+        ("a ** b ** c", "(a ** b) ** c"),
+        // unary operators
+        ("- a ^ b", "(- a) ^ b"),
+        ("a ^ - b", "a ^ (- b)"),
+        ("- a * b", "(- a) * b"),
+        ("- a + b", "(- a) + b"),
+        ("~ a & b", "(~ a) & b"),
+        ("a & ~ b", "a & (~ b)"),
+        ("- - a", "- (- a)"),
+        ("NOT - a = b", "NOT ((- a) = b)"),
+        ("NOT ~ a IS NULL", "NOT ((~ a) IS NULL)"),
+        // ::
+        ("- a::INT", "- (a::INT)"),
+        ("~ a::INT", "~ (a::INT)"),
+        ("NOT - a::INT", "NOT (- (a::INT))"),
+        ("a ^ b::INT", "a ^ (b::INT)"),
+        ("a::INT ^ b", "(a::INT) ^ b"),
+        ("a + b::INT", "a + (b::INT)"),
+        ("a::INT::TEXT", "(a::INT)::TEXT"),
+        ("a::INT IS NULL", "(a::INT) IS NULL"),
+        ("a::INT IN (1, 2)", "(a::INT) IN (1, 2)"),
+    ];
+    for (sql, parenthesized) in cases {
+        assert_grouping(sql, parenthesized);
+    }
+}
+
+/// The binary operators by level from loosest to tightest, after the PostgreSQL
+/// operator precedence table. The operators of a level group to the left, the
+/// comparisons and `LIKE` are non-associative in PostgreSQL.
+const LEVELS: &[&[&str]] = &[
+    &["OR"],
+    &["AND"],
+    &["IS DISTINCT FROM", "IS NOT DISTINCT FROM"],
+    &["=", "<>", "!=", "<", ">", "<=", ">="],
+    &["LIKE", "NOT LIKE", "ILIKE"],
+    &["||", "|", "&", "<<", ">>"],
+    &["+", "-"],
+    &["*", "/", "%"],
+    &["^"],
+];
+
+/// The prefix operators with the first level of [`LEVELS`] that binds tighter
+const PREFIX_LEVELS: &[(&str, usize)] = &[("NOT", 2), ("-", LEVELS.len()), ("~", LEVELS.len())];
+
+/// Every pair of binary operators, `a op1 b op2 c`.
+#[test]
+pub fn test_binary_operator_pairs() {
+    for (level1, ops1) in LEVELS.iter().enumerate() {
+        for (level2, ops2) in LEVELS.iter().enumerate() {
+            for op1 in *ops1 {
+                for op2 in *ops2 {
+                    let sql = format!("a {op1} b {op2} c");
+                    let parenthesized = if level1 >= level2 {
+                        format!("(a {op1} b) {op2} c")
+                    } else {
+                        format!("a {op1} (b {op2} c)")
+                    };
+                    assert_grouping(&sql, &parenthesized);
+                }
+            }
+        }
+    }
+}
+
+/// Every prefix operator before every binary operator, `op1 a op2 b`.
+#[test]
+pub fn test_prefix_operator_pairs() {
+    for (op1, tighter) in PREFIX_LEVELS {
+        for (level, ops) in LEVELS.iter().enumerate() {
+            for op2 in *ops {
+                let sql = format!("{op1} a {op2} b");
+                let parenthesized = if level >= *tighter {
+                    format!("{op1} (a {op2} b)")
+                } else {
+                    format!("({op1} a) {op2} b")
+                };
+                assert_grouping(&sql, &parenthesized);
+            }
+        }
+    }
+}
+
+/// Checks that the expression parses to the same tree as its parenthesized form
+/// and that unparsing it keeps the grouping.
+fn assert_grouping(sql: &str, parenthesized: &str) {
+    let sql = format!("SELECT {sql}");
+    let parenthesized = format!("SELECT {parenthesized}");
+    let parsed = parse_query(&sql).unwrap_or_else(|e| panic!("{sql}: {e}"));
+    let expected = parse_query(&parenthesized).unwrap_or_else(|e| panic!("{parenthesized}: {e}"));
+    assert_eq!(parsed, expected, "{sql} should be {parenthesized}");
+
+    let unparsed = parsed.to_string();
+    assert_eq!(
+        parse_query(&unparsed).unwrap(),
+        parsed,
+        "{sql} unparsed as {unparsed}"
+    );
+}
@@ -1,4 +1,4 @@
-use sqlparser_nom::parse_query;
+use sqlparser_nom::{parse_query, parse_statements, parse_statements_with_recovery};
 
 #[test]
 pub fn test_query() {
@@ -11,7 +11,7 @@ pub fn test_query() {
         // select
         (
             r#"SELECT a, b, a + b FROM table"#,
-            r#"SELECT a, b, (a + b) FROM table"#,
+            r#"SELECT a, b, a + b FROM table"#,
         ),
         (
             r#"SELECT DISTINCT person, age FROM employees"#,
@@ -25,29 +25,33 @@ pub fn test_query() {
         // where
         (
             r#"SELECT a FROM table WHERE a > 10"#,
-            r#"SELECT a FROM table WHERE (a > 10)"#,
+            r#"SELECT a FROM table WHERE a > 10"#,
         ),
         // join
         (
             r#"select * from x inner join x y ON x.column_1 = y.column_1;"#,
-            r#"SELECT * FROM (x INNER JOIN x AS y ON (x.column_1 = y.column_1))"#,
+            r#"SELECT * FROM x INNER JOIN x AS y ON x.column_1 = y.column_1"#,
         ),
         (
             r#"select * from x left join x y ON x.column_1 = y.column_2;"#,
-            r#"SELECT * FROM (x LEFT OUTER JOIN x AS y ON (x.column_1 = y.column_2))"#,
+            r#"SELECT * FROM x LEFT OUTER JOIN x AS y ON x.column_1 = y.column_2"#,
         ),
         (
             r#"select * from x right join x y ON x.column_1 = y.column_2;"#,
-            r#"SELECT * FROM (x RIGHT OUTER JOIN x AS y ON (x.column_1 = y.column_2))"#,
+            r#"SELECT * FROM x RIGHT OUTER JOIN x AS y ON x.column_1 = y.column_2"#,
         ),
         (
             r#"select * from x full outer join x y ON x.column_1 = y.column_2;"#,
-            r#"SELECT * FROM (x FULL OUTER JOIN x AS y ON (x.column_1 = y.column_2))"#,
+            r#"SELECT * FROM x FULL OUTER JOIN x AS y ON x.column_1 = y.column_2"#,
         ),
         // (r#"select * from x natural join x y;"#, r#""#),
         (
             r#"select * from x cross join x y;"#,
-            r#"SELECT * FROM (x CROSS JOIN x AS y)"#,
+            r#"SELECT * FROM x CROSS JOIN x AS y"#,
+        ),
+        (
+            r#"SELECT a FROM table WHERE name = 'bob' AND price > 1.5 OR flag = true OR x = null"#,
+            r#"SELECT a FROM table WHERE name = 'bob' AND price > 1.5 OR flag = TRUE OR x = NULL"#,
         ),
         // group by
         (
@@ -57,7 +61,7 @@ pub fn test_query() {
         // having
         (
             r#"SELECT a, b, MAX(c) FROM table GROUP BY a, b HAVING MAX(c) > 10"#,
-            r#"SELECT a, b, MAX(c) FROM table GROUP BY a, b Having (MAX(c) > 10)"#,
+            r#"SELECT a, b, MAX(c) FROM table GROUP BY a, b HAVING MAX(c) > 10"#,
         ),
         // order by
         (
@@ -86,14 +90,18 @@ pub fn test_query() {
             r#"SELECT * EXCLUDE(age, person) FROM table;"#,
             r#"SELECT * EXCLUDE (age, person) FROM table"#,
         ),
+        (
+            r#"SELECT * EXCLUDE age FROM table;"#,
+            r#"SELECT * EXCLUDE age FROM table"#,
+        ),
         // subquery
         (
             r#"select * from x y where exists (select * from x where x.column_1 = y.column_1);"#,
-            r#"SELECT * FROM x AS y WHERE EXISTS (SELECT * FROM x WHERE (x.column_1 = y.column_1))"#,
+            r#"SELECT * FROM x AS y WHERE EXISTS (SELECT * FROM x WHERE x.column_1 = y.column_1)"#,
         ),
         (
             r#"select * from x y where not exists (select * from x where x.column_1 = y.column_1);"#,
-            r#"SELECT * FROM x AS y WHERE NOT EXISTS (SELECT * FROM x WHERE (x.column_1 = y.column_1))"#,
+            r#"SELECT * FROM x AS y WHERE NOT EXISTS (SELECT * FROM x WHERE x.column_1 = y.column_1)"#,
         ),
         (
             r#"select * from x where column_1 in (select column_1 from x);"#,
@@ -113,7 +121,83 @@ pub fn test_query() {
         ),
         (
             r#"select * from x y where column_1 < (select sum(column_2) from x where x.column_1 = y.column_1);"#,
-            r#"SELECT * FROM x AS y WHERE (column_1 < (SELECT sum(column_2) FROM x WHERE (x.column_1 = y.column_1)))"#,
+            r#"SELECT * FROM x AS y WHERE column_1 < (SELECT sum(column_2) FROM x WHERE x.column_1 = y.column_1)"#,
+        ),
+        // operators
+        (
+            r#"select id % 2, a // 2, first_name || ' ' || last_name, flags & 4 | 1, ~mask, 2 ** 10 << 1 from t;"#,
+            r#"SELECT id % 2, a // 2, first_name || ' ' || last_name, flags & 4 | 1, ~mask, 2 ** 10 << 1 FROM t"#,
+        ),
+        // is
+        (
+            r#"select * from users where deleted_at is null and verified is not false;"#,
+            r#"SELECT * FROM users WHERE deleted_at IS NULL AND verified IS NOT FALSE"#,
+        ),
+        (
+            r#"select * from x join y on x.a is not distinct from y.a;"#,
+            r#"SELECT * FROM x INNER JOIN y ON x.a IS NOT DISTINCT FROM y.a"#,
+        ),
+        // between
+        (
+            r#"select * from orders where amount between 10 and 100 and status not between 3 and 5;"#,
+            r#"SELECT * FROM orders WHERE amount BETWEEN 10 AND 100 AND status NOT BETWEEN 3 AND 5"#,
+        ),
+        // pattern matching
+        (
+            r#"select * from users where name like 'a%' and email not ilike any ('%@a.com', '%@b.com');"#,
+            r#"SELECT * FROM users WHERE name LIKE 'a%' AND email NOT ILIKE ANY ('%@a.com', '%@b.com')"#,
+        ),
+        (
+            r#"select * from t where a similar to '%(b|d)%' escape '!' or a regexp '^b';"#,
+            r#"SELECT * FROM t WHERE a SIMILAR TO '%(b|d)%' ESCAPE '!' OR a REGEXP '^b'"#,
+        ),
+        // cast
+        (
+            r#"select cast(price as decimal(10,2)), try_cast(code as int), created_at::date from t;"#,
+            r#"SELECT CAST(price AS DECIMAL(10, 2)), TRY_CAST(code AS INT), created_at::DATE FROM t"#,
+        ),
+        // case
+        (
+            r#"select case when status = 1 then 'a' when status = 2 then 'b' else 'c' end as label from t;"#,
+            r#"SELECT CASE WHEN status = 1 THEN 'a' WHEN status = 2 THEN 'b' ELSE 'c' END AS label FROM t"#,
+        ),
+        (
+            r#"select sum(case x when 1 then case when y > 0 then y end else 0 end) from t;"#,
+            r#"SELECT sum(CASE x WHEN 1 THEN CASE WHEN y > 0 THEN y END ELSE 0 END) FROM t"#,
+        ),
+        // set operation
+        (
+            r#"select a from t union all select a from u order by a limit 10;"#,
+            r#"SELECT a FROM t UNION ALL SELECT a FROM u ORDER BY a LIMIT 10"#,
+        ),
+        (
+            r#"select a from t except distinct select a from u intersect select a from v;"#,
+            r#"SELECT a FROM t EXCEPT DISTINCT SELECT a FROM u INTERSECT SELECT a FROM v"#,
+        ),
+        (
+            r#"(select a from t order by a limit 1) union (select a from u order by a desc limit 1);"#,
+            r#"(SELECT a FROM t ORDER BY a LIMIT 1) UNION (SELECT a FROM u ORDER BY a DESC LIMIT 1)"#,
+        ),
+        (
+            r#"select * except (b) from t except select * except (b) from u;"#,
+            r#"SELECT * EXCEPT (b) FROM t EXCEPT SELECT * EXCEPT (b) FROM u"#,
+        ),
+        (
+            r#"select * from (select a from t union select a from u) as x where a in (select 1 intersect select 2);"#,
+            r#"SELECT * FROM (SELECT a FROM t UNION SELECT a FROM u) AS x WHERE a IN (SELECT 1 INTERSECT SELECT 2)"#,
+        ),
+        // values
+        (
+            r#"values (1, 'a'), (2, 'b') order by 1;"#,
+            r#"VALUES (1, 'a'), (2, 'b') ORDER BY 1"#,
+        ),
+        (
+            r#"select a, b from t union all values (1 + 1, null);"#,
+            r#"SELECT a, b FROM t UNION ALL VALUES (1 + 1, NULL)"#,
+        ),
+        (
+            r#"select v.a from (values (1, 2), (3, 4)) as v(a, b) join t x(c) on v.b = x.c;"#,
+            r#"SELECT v.a FROM (VALUES (1, 2), (3, 4)) AS v (a, b) INNER JOIN t AS x (c) ON v.b = x.c"#,
         ),
         // window function
         (
@@ -124,7 +208,31 @@ pub fn test_query() {
             r#"SELECT depname, empno, salary, rank() OVER (PARTITION BY depname ORDER BY salary DESC) FROM empsalary;"#,
             r#"SELECT depname, empno, salary, rank() OVER (PARTITION BY depname ORDER BY salary DESC) FROM empsalary"#,
         ),
-        // (r#"SELECT depname, empno, salary, avg(salary) OVER(ORDER BY salary ASC ROWS BETWEEN 1 PRECEDING AND 1 FOLLOWING) AS avg, min(salary) OVER(ORDER BY empno ASC ROWS BETWEEN UNBOUNDED PRECEDING AND CURRENT ROW) AS cum_min FROM empsalary ORDER BY empno ASC;"#, r#""#),
+        (
+            r#"SELECT depname, empno, salary, avg(salary) OVER(ORDER BY salary ASC ROWS BETWEEN 1 PRECEDING AND 1 FOLLOWING) AS avg, min(salary) OVER(ORDER BY empno ASC ROWS BETWEEN UNBOUNDED PRECEDING AND CURRENT ROW) AS cum_min FROM empsalary ORDER BY empno ASC;"#,
+            r#"SELECT depname, empno, salary, avg(salary) OVER (ORDER BY salary ASC ROWS BETWEEN 1 PRECEDING AND 1 FOLLOWING) AS avg, min(salary) OVER (ORDER BY empno ASC ROWS BETWEEN UNBOUNDED PRECEDING AND CURRENT ROW) AS cum_min FROM empsalary ORDER BY empno ASC"#,
+        ),
+        (
+            r#"select sum(x) over (order by d range between interval '1' day preceding and current row exclude ties), count(*) over (groups unbounded preceding exclude no others) from t;"#,
+            r#"SELECT sum(x) OVER (ORDER BY d RANGE BETWEEN INTERVAL '1' DAY PRECEDING AND CURRENT ROW EXCLUDE TIES), count(*) OVER (GROUPS UNBOUNDED PRECEDING EXCLUDE NO OTHERS) FROM t"#,
+        ),
+        (
+            r#"select a, rank() over (partition by b order by c desc nulls last) from t order by a nulls first, b asc nulls last;"#,
+            r#"SELECT a, rank() OVER (PARTITION BY b ORDER BY c DESC NULLS LAST) FROM t ORDER BY a NULLS FIRST, b ASC NULLS LAST"#,
+        ),
+        (
+            r#"select sum(x) over (w order by b rows between current row and unbounded following exclude group), rows, range from t window w as (partition by a);"#,
+            r#"SELECT sum(x) OVER (w ORDER BY b ROWS BETWEEN CURRENT ROW AND UNBOUNDED FOLLOWING EXCLUDE GROUP), rows, range FROM t WINDOW w AS (PARTITION BY a)"#,
+        ),
+        // aggregate clauses
+        (
+            r#"select count(*) filter (where x > 0), percentile_cont(0.5) within group (order by v desc), string_agg(name, ',' order by name nulls first) from t;"#,
+            r#"SELECT count(*) FILTER (WHERE x > 0), percentile_cont(0.5) WITHIN GROUP (ORDER BY v DESC), string_agg(name, ',' ORDER BY name NULLS FIRST) FROM t"#,
+        ),
+        (
+            r#"select array_agg(distinct x order by y limit 10) filter (where y is not null), first_value(x ignore nulls) over w, last_value(x respect nulls) over (order by y) as filter, within from t window w as (order by y);"#,
+            r#"SELECT array_agg(DISTINCT x ORDER BY y LIMIT 10) FILTER (WHERE y IS NOT NULL), first_value(x IGNORE NULLS) OVER w, last_value(x RESPECT NULLS) OVER (ORDER BY y) AS filter, within FROM t WINDOW w AS (ORDER BY y)"#,
+        ),
         (
             r#"SELECT sum(salary) OVER w, avg(salary) OVER w FROM empsalary WINDOW w AS (PARTITION BY depname ORDER BY salary DESC);"#,
             r#"SELECT sum(salary) OVER w, avg(salary) OVER w FROM empsalary WINDOW w AS (PARTITION BY depname ORDER BY salary DESC)"#,
@@ -136,5 +244,518 @@ pub fn test_query() {
         assert!(result.is_ok());
         let result = result.unwrap();
         assert_eq!(output, &result.to_string());
+        assert_eq!(parse_query(output).unwrap(), result);
+    }
+}
+
+#[test]
+pub fn test_lexical_error() {
+    let cases = [
+        ("SELECT 'abc FROM t", "unterminated quoted string"),
+        ("SELECT a FROM t /* comment", "unterminated block comment"),
+        ("SELECT @a FROM t", "invalid character `@`"),
+        ("SELECT 1x FROM t", "malformed number"),
+    ];
+    for (input, message) in &cases {
+        let result = parse_query(input);
+        assert!(result.is_err());
+        assert_eq!(message, &result.unwrap_err().to_string());
+    }
+}
+
+#[test]
+pub fn test_trailing_tokens() {
+    let cases = [
+        (r#"SELECT a FROM t;;"#, "unexpected `;`"),
+        (r#"SELECT a FROM t WHERE"#, "unexpected end of input"),
+        (r#"SELECT a FROM t x y"#, "unexpected identifier"),
+        (r#"SELECT a b"#, "unexpected identifier"),
+    ];
+    for (input, message) in &cases {
+        let result = parse_query(input);
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().starts_with(message));
+    }
+}
+
+/// Words that only have a meaning next to an operator or inside a clause are
+/// not reserved, they still name columns and aliases.
+#[test]
+pub fn test_non_reserved_words() {
+    let words = [
+        "unknown",
+        "to",
+        "escape",
+        "regexp",
+        "rlike",
+        "similar",
+        "ilike",
+        "any",
+        "all",
+        "symmetric",
+        "try_cast",
+        "safe_cast",
+        "nulls",
+    ];
+    let mut inputs = vec![];
+    for word in words {
+        inputs.push(format!("SELECT {word} FROM t"));
+        inputs.push(format!("SELECT t.{word} AS {word} FROM t AS {word}"));
+        inputs.push(format!("SELECT {word} FROM t {word}"));
+    }
+    // next to the operators they belong to
+    inputs.extend(
+        [
+            "SELECT unknown IS UNKNOWN FROM t",
+            "SELECT escape LIKE similar ESCAPE '!', to SIMILAR TO regexp FROM t",
+            "SELECT regexp REGEXP rlike, rlike NOT RLIKE ilike, ilike ILIKE any FROM t",
+            "SELECT any LIKE ANY (all), all NOT LIKE ALL (any, 'a%') FROM t",
+            "SELECT all FROM t UNION ALL SELECT any FROM u",
+            "SELECT first_value(nulls IGNORE NULLS) OVER (ORDER BY nulls NULLS LAST) FROM t ORDER BY nulls DESC NULLS FIRST",
+            "SELECT try_cast(a), TRY_CAST(a AS INT), safe_cast(b), SAFE_CAST(b AS TEXT) FROM t",
+            "SELECT a BETWEEN symmetric AND 1, a NOT BETWEEN SYMMETRIC symmetric AND 1 FROM t",
+        ]
+        .map(String::from),
+    );
+    for input in inputs {
+        let result = parse_query(&input).unwrap_or_else(|e| panic!("{input}: {e}"));
+        assert_eq!(parse_query(&result.to_string()).unwrap(), result);
+    }
+}
+
+/// Words reserved since the first release, they only name columns after a `.`
+#[test]
+pub fn test_reserved_words() {
+    let words = [
+        "case",
+        "when",
+        "then",
+        "else",
+        "end",
+        "cast",
+        "union",
+        "intersect",
+        "values",
+    ];
+    for word in words {
+        for input in [
+            format!("SELECT {word} FROM t"),
+            format!("SELECT a AS {word} FROM t"),
+            format!("SELECT a FROM t AS {word}"),
+        ] {
+            assert!(parse_query(&input).is_err(), "{input}");
+        }
+        let input = format!("SELECT t.{word}, db.{word}.{word} FROM t");
+        let result = parse_query(&input).unwrap_or_else(|e| panic!("{input}: {e}"));
+        assert_eq!(result.to_string(), input);
+    }
+}
+
+#[test]
+pub fn test_statements() {
+    let sql = r#"
+        SELECT a FROM t;
+        ;
+        WITH x AS (SELECT 1) SELECT * FROM x;
+        SELECT b FROM u
+    "#;
+    let stmts = parse_statements(sql).unwrap();
+    assert_eq!(
+        stmts.iter().map(|s| s.to_string()).collect::<Vec<_>>(),
+        vec![
+            "SELECT a FROM t",
+            "WITH x AS (SELECT 1) SELECT * FROM x",
+            "SELECT b FROM u",
+        ]
+    );
+    assert!(parse_statements("  ;; ").unwrap().is_empty());
+
+    let cases = [
+        (
+            "SELECT 1; SELECT a FROM t WHERE; SELECT 2",
+            1,
+            "unexpected `;`",
+        ),
+        (
+            "SELECT 1;; SELECT 2; SELECT a b",
+            2,
+            "unexpected identifier",
+        ),
+        ("SELECT 1; SELECT 'a", 1, "unterminated quoted string"),
+        ("SELECT 1 SELECT 2", 0, "unexpected `SELECT`"),
+    ];
+    for (input, index, message) in &cases {
+        let error = parse_statements(input).unwrap_err();
+        assert_eq!(index, &error.index);
+        assert!(error.error.to_string().starts_with(message));
+    }
+}
+
+#[test]
+pub fn test_recovery() {
+    let sql =
+        "SELECT a, b +, c FROM t WHERE a > GROUP BY a; SELECT (1; SELECT 2; ) SELECT 3; SELECT @ 4";
+    let (stmts, diagnostics) = parse_statements_with_recovery(sql);
+    assert_eq!(
+        stmts.iter().map(|s| s.to_string()).collect::<Vec<_>>(),
+        vec![
+            "SELECT a, <error>, c FROM t WHERE <error> GROUP BY a",
+            "SELECT <error>",
+            "SELECT 2",
+            "<error>",
+            "SELECT 4",
+        ]
+    );
+    assert_eq!(
+        diagnostics
+            .iter()
+            .map(|e| (e.span.clone(), e.to_string()))
+            .collect::<Vec<_>>(),
+        vec![
+            (13..14, "unexpected `,`, expected one of identifier, string, integer, float, `+`, `-`, `~`, `(`, `CASE`, `CAST`, `EXISTS`, `FALSE`, `NOT`, `NULL`, `SELECT`, `TRUE`".to_string()),
+            (34..39, "unexpected `GROUP`, expected one of identifier, string, integer, float, `+`, `-`, `~`, `(`, `CASE`, `CAST`, `EXISTS`, `FALSE`, `NOT`, `NULL`, `SELECT`, `TRUE`".to_string()),
+            (55..56, "unexpected `;`, expected one of identifier, `=`, `!=`, `<`, `>`, `<=`, `>=`, `+`, `-`, `*`, `/`, `//`, `%`, `||`, `&`, `|`, `^`, `<<`, `**`, `)`, `::`, `AND`, `BETWEEN`, `IN`, `IS`, `LIKE`, `NOT`, `OR`".to_string()),
+            (67..68, "unexpected `)`, expected one of `(`, `SELECT`, `VALUES`, `WITH`".to_string()),
+            (86..87, "invalid character `@`".to_string()),
+        ]
+    );
+
+    // a set operator ends the broken part of an operand
+    let (stmts, diagnostics) =
+        parse_statements_with_recovery("SELECT a + FROM t WHERE UNION SELECT b");
+    assert_eq!(
+        stmts[0].to_string(),
+        "SELECT <error> FROM t WHERE <error> UNION SELECT b"
+    );
+    assert_eq!(
+        diagnostics
+            .iter()
+            .map(|e| e.span.clone())
+            .collect::<Vec<_>>(),
+        vec![11..15, 24..29]
+    );
+}
+
+#[test]
+pub fn test_spans() {
+    use sqlparser_nom::ast::{set_expr::SetExpr, statement::Statement, Spanned};
+
+    let sql = "WITH x AS (SELECT a FROM t)\nSELECT a + b * 2 AS c, (d), * FROM x JOIN y ON x.a = y.a WHERE e IN (1, 2) ORDER BY a DESC;";
+    let stmt = parse_query(sql).unwrap();
+    assert_eq!(&sql[stmt.span.range()], &sql[..sql.len() - 1]);
+    assert_eq!(
+        &sql[stmt.with.as_ref().unwrap().cte_tables[0].span.range()],
+        "x AS (SELECT a FROM t)"
+    );
+    let SetExpr::Select {
+        projection,
+        from,
+        selection,
+        ..
+    } = &stmt.body
+    else {
+        panic!("expected a select, got {:?}", stmt.body);
+    };
+    assert_eq!(
+        projection
+            .iter()
+            .map(|item| &sql[item.span().range()])
+            .collect::<Vec<_>>(),
+        vec!["a + b * 2 AS c", "d", "*"]
+    );
+    assert_eq!(
+        &sql[from.as_ref().unwrap().span().range()],
+        "x JOIN y ON x.a = y.a"
+    );
+    assert_eq!(
+        &sql[selection.as_ref().unwrap().span().range()],
+        "e IN (1, 2)"
+    );
+    assert_eq!(&sql[stmt.order_by[0].span.range()], "a DESC");
+
+    let sql = "SELECT 1; WITH x; SELECT a +";
+    let (stmts, _) = parse_statements_with_recovery(sql);
+    assert!(matches!(stmts[1], Statement::Error { .. }));
+    assert_eq!(&sql[stmts[1].span().range()], "WITH x");
+    let Statement::Select(stmt) = &stmts[2] else {
+        panic!("should be a select")
+    };
+    let SetExpr::Select { projection, .. } = &stmt.body else {
+        panic!("expected a select, got {:?}", stmt.body);
+    };
+    assert_eq!(&sql[projection[0].span().range()], "a +");
+}
+
+#[test]
+pub fn test_equality() {
+    use std::collections::HashSet;
+
+    use sqlparser_nom::ast::expr::{Literal, Number};
+    use sqlparser_nom::ast::set_expr::SetExpr;
+    use sqlparser_nom::ast::Span;
+
+    // spans are ignored
+    assert_eq!(
+        parse_query("select a+1 from t where b in (1,2)").unwrap(),
+        parse_query("SELECT a + 1\nFROM t\nWHERE b IN (1, 2);").unwrap()
+    );
+    assert_ne!(
+        parse_query("SELECT a + 1 FROM t").unwrap(),
+        parse_query("SELECT a + 2 FROM t").unwrap()
+    );
+    // while spans themselves compare by value
+    let (a, b) = (
+        parse_query("SELECT a + 1 FROM t").unwrap(),
+        parse_query("SELECT  a + 1 FROM t").unwrap(),
+    );
+    assert_eq!(a, b);
+    assert_ne!(*a.span, *b.span);
+    assert_eq!(*a.span, Span::new(0, 19));
+
+    let stmt = parse_query("SELECT a + 1, b, a + 1, (a + 1), b FROM t").unwrap();
+    let SetExpr::Select { projection, .. } = &stmt.body else {
+        panic!("expected a select, got {:?}", stmt.body);
+    };
+    assert_eq!(projection.iter().collect::<HashSet<_>>().len(), 2);
+    let mut sorted = projection.clone();
+    sorted.sort();
+    sorted.dedup();
+    assert_eq!(sorted.len(), 2);
+
+    // numbers compare by their text, as written
+    let number = |text: &str| Literal::Number(Number::new(text));
+    assert_eq!(number("1.5"), number("1.5"));
+    assert_ne!(number("1.5"), number("1.50"));
+    assert!(Literal::String("1".to_string()) < number("1"));
+    assert_eq!(
+        [number("0.1"), number("0.1"), number("1e-1")]
+            .into_iter()
+            .collect::<HashSet<_>>()
+            .len(),
+        2
+    );
+}
+
+#[test]
+pub fn test_unparse() {
+    let cases = [
+        ("SELECT (a + b) + c", "SELECT a + b + c"),
+        ("SELECT a + (b + c)", "SELECT a + (b + c)"),
+        ("SELECT a - (b - c)", "SELECT a - (b - c)"),
+        ("SELECT (a + b) * c", "SELECT (a + b) * c"),
+        ("SELECT a + (b * c)", "SELECT a + b * c"),
+        ("SELECT (a / b) / (c * d)", "SELECT a / b / (c * d)"),
+        (
+            "SELECT -(a + b), -(-a), +(-a), -(a) * b",
+            "SELECT -(a + b), - -a, + -a, -a * b",
+        ),
+        (
+            "SELECT (a OR b) AND NOT EXISTS (SELECT 1)",
+            "SELECT (a OR b) AND NOT EXISTS (SELECT 1)",
+        ),
+        (
+            "(SELECT 1 UNION SELECT 2) UNION (SELECT 3 UNION SELECT 4)",
+            "SELECT 1 UNION SELECT 2 UNION (SELECT 3 UNION SELECT 4)",
+        ),
+        (
+            "(SELECT 1 UNION SELECT 2) INTERSECT SELECT 3 INTERSECT (SELECT 4)",
+            "(SELECT 1 UNION SELECT 2) INTERSECT SELECT 3 INTERSECT SELECT 4",
+        ),
+        (
+            "SELECT 1 EXCEPT (SELECT 2 INTERSECT SELECT 3)",
+            "SELECT 1 EXCEPT SELECT 2 INTERSECT SELECT 3",
+        ),
+        (
+            "WITH x AS (SELECT 1) (SELECT * FROM x LIMIT 1) UNION ALL ((SELECT 2))",
+            "WITH x AS (SELECT 1) (SELECT * FROM x LIMIT 1) UNION ALL SELECT 2",
+        ),
+        (
+            "SELECT * FROM t WHERE NOT (a = 1) AND NOT active",
+            "SELECT * FROM t WHERE NOT a = 1 AND NOT active",
+        ),
+        (
+            "SELECT NOT (NOT a), NOT (EXISTS (SELECT 1)), (NOT a) = b",
+            "SELECT NOT NOT a, NOT (EXISTS (SELECT 1)), (NOT a) = b",
+        ),
+        (
+            "SELECT a = 1 OR (b = 2 AND c = 3)",
+            "SELECT a = 1 OR b = 2 AND c = 3",
+        ),
+        (
+            "SELECT (a = b) = c, a = (b = c)",
+            "SELECT a = b = c, a = (b = c)",
+        ),
+        (
+            "SELECT (a + 1) IN (1), a = (b IN (1)), (a = b) IN (1)",
+            "SELECT a + 1 IN (1), a = b IN (1), (a = b) IN (1)",
+        ),
+        (
+            "SELECT * FROM t WHERE a = 1 OR (b IN (1, 2))",
+            "SELECT * FROM t WHERE a = 1 OR b IN (1, 2)",
+        ),
+        (
+            "SELECT * FROM t WHERE NOT (a IN (SELECT a FROM u))",
+            "SELECT * FROM t WHERE NOT a IN (SELECT a FROM u)",
+        ),
+        ("SELECT (a IN (1)) + 1", "SELECT (a IN (1)) + 1"),
+        ("SELECT f((a + b) * 2, (c))", "SELECT f((a + b) * 2, c)"),
+        (
+            "SELECT * FROM a JOIN (b JOIN c ON (b.x = c.x)) ON (a.x = b.x)",
+            "SELECT * FROM a INNER JOIN (b INNER JOIN c ON b.x = c.x) ON a.x = b.x",
+        ),
+        (
+            "SELECT * FROM ((a JOIN b) JOIN c) CROSS JOIN (d)",
+            "SELECT * FROM a INNER JOIN b INNER JOIN c CROSS JOIN d",
+        ),
+    ];
+    for (input, output) in cases {
+        let ast = parse_query(input).unwrap();
+        assert_eq!(ast.to_string(), output);
+        assert_eq!(parse_query(output).unwrap(), ast, "{output}");
+    }
+}
+
+#[test]
+pub fn test_unparse_expr() {
+    let cases = [
+        // operators
+        ("a % 2 = 0", "a % 2 = 0"),
+        (
+            "first_name || ' ' || last_name",
+            "first_name || ' ' || last_name",
+        ),
+        ("2 ** 3 ** 2", "2 ** 3 ** 2"),
+        ("-a ^ 2", "-a ^ 2"),
+        ("~a & b", "~a & b"),
+        ("a = (NOT b)", "a = (NOT b)"),
+        ("NOT a NOT LIKE 'x%'", "NOT a NOT LIKE 'x%'"),
+        ("NOT (a AND b)", "NOT (a AND b)"),
+        // IS
+        ("deleted_at is null", "deleted_at IS NULL"),
+        ("a IS NOT NULL AND b", "a IS NOT NULL AND b"),
+        ("a = b IS TRUE", "a = b IS TRUE"),
+        (
+            "a IS NOT FALSE OR b IS UNKNOWN",
+            "a IS NOT FALSE OR b IS UNKNOWN",
+        ),
+        ("a is distinct from b + 1", "a IS DISTINCT FROM b + 1"),
+        (
+            "a IS NOT DISTINCT FROM (b AND c)",
+            "a IS NOT DISTINCT FROM (b AND c)",
+        ),
+        ("(a AND b) IS NULL", "(a AND b) IS NULL"),
+        // BETWEEN
+        ("a between 1 and 2", "a BETWEEN 1 AND 2"),
+        ("a BETWEEN 1 AND 2 AND b > 3", "a BETWEEN 1 AND 2 AND b > 3"),
+        (
+            "a NOT BETWEEN b + 1 AND c * 2",
+            "a NOT BETWEEN b + 1 AND c * 2",
+        ),
+        (
+            "a between symmetric 10 and 1",
+            "a BETWEEN SYMMETRIC 10 AND 1",
+        ),
+        ("a BETWEEN (b AND c) AND d", "a BETWEEN (b AND c) AND d"),
+        // a column named `symmetric`
+        ("a between symmetric and 1", "a BETWEEN (symmetric) AND 1"),
+        (
+            "a between symmetric symmetric and 1",
+            "a BETWEEN SYMMETRIC symmetric AND 1",
+        ),
+        (
+            "a between (symmetric + 1) and 2",
+            "a BETWEEN (symmetric + 1) AND 2",
+        ),
+        (
+            "a between (symmetric::int = b.c) and 2",
+            "a BETWEEN (symmetric::INT = b.c) AND 2",
+        ),
+        (
+            "a between (symmetric(1) || 'x') and 2",
+            "a BETWEEN (symmetric(1) || 'x') AND 2",
+        ),
+        (
+            "a between t.symmetric + 1 and 2",
+            "a BETWEEN t.symmetric + 1 AND 2",
+        ),
+        (
+            "a between -symmetric and (symmetric)",
+            "a BETWEEN -symmetric AND symmetric",
+        ),
+        (
+            "a between (symmetric + b) * c and 2",
+            "a BETWEEN (symmetric + b) * c AND 2",
+        ),
+        ("a BETWEEN 1 AND 2 = TRUE", "a BETWEEN 1 AND 2 = TRUE"),
+        ("a = (b BETWEEN 1 AND 2)", "a = b BETWEEN 1 AND 2"),
+        ("(a = b) BETWEEN 1 AND 2", "(a = b) BETWEEN 1 AND 2"),
+        // casts
+        ("cast(a as int)", "CAST(a AS INT)"),
+        (
+            "try_cast(a + 1 AS decimal(10, 2))",
+            "TRY_CAST(a + 1 AS DECIMAL(10, 2))",
+        ),
+        ("SAFE_CAST(a AS STRING)", "SAFE_CAST(a AS STRING)"),
+        ("CAST(a AS INTERVAL DAY)", "CAST(a AS INTERVAL DAY)"),
+        (
+            "cast(a as interval hour to minute) + b",
+            "CAST(a AS INTERVAL HOUR TO MINUTE) + b",
+        ),
+        ("CAST(a AS foo.bar)", "CAST(a AS foo.bar)"),
+        ("a::public.my_type[]", "a::public.my_type[]"),
+        ("a::int", "a::INT"),
+        ("'1'::integer::text[]", "'1'::INT::TEXT[]"),
+        ("(a + b)::bigint", "(a + b)::BIGINT"),
+        ("a + b::bigint", "a + b::BIGINT"),
+        ("-a::int", "-a::INT"),
+        ("(-a)::int", "(-a)::INT"),
+        (
+            "a::timestamp with time zone > b",
+            "a::TIMESTAMP WITH TIME ZONE > b",
+        ),
+        ("CAST(a AS timestamp(3))", "CAST(a AS TIMESTAMP(3))"),
+        ("a::time(6) with time zone", "a::TIME(6) WITH TIME ZONE"),
+        // CASE
+        (
+            "case when a = 1 then 'x' end",
+            "CASE WHEN a = 1 THEN 'x' END",
+        ),
+        (
+            "CASE a + 1 WHEN 1 THEN 'x' WHEN 2 THEN 'y' ELSE 'z' END",
+            "CASE a + 1 WHEN 1 THEN 'x' WHEN 2 THEN 'y' ELSE 'z' END",
+        ),
+        (
+            "CASE WHEN a THEN CASE b WHEN 1 THEN c END ELSE d END = e",
+            "CASE WHEN a THEN CASE b WHEN 1 THEN c END ELSE d END = e",
+        ),
+        // pattern matching
+        ("name like 'a%'", "name LIKE 'a%'"),
+        (
+            "name NOT ILIKE 'a%' ESCAPE '!'",
+            "name NOT ILIKE 'a%' ESCAPE '!'",
+        ),
+        ("name similar to '(a|b)%'", "name SIMILAR TO '(a|b)%'"),
+        (
+            "name not similar to 'a' escape ''",
+            "name NOT SIMILAR TO 'a' ESCAPE ''",
+        ),
+        ("name REGEXP '^a' AND b", "name REGEXP '^a' AND b"),
+        ("name not rlike '^a'", "name NOT RLIKE '^a'"),
+        (
+            "name LIKE ANY ('a%', 'b%') ESCAPE '\\\\'",
+            "name LIKE ANY ('a%', 'b%') ESCAPE '\\\\'",
+        ),
+        ("name NOT LIKE ALL ('a%', b)", "name NOT LIKE ALL ('a%', b)"),
+        ("a LIKE (b LIKE c)", "a LIKE (b LIKE c)"),
+        // literals
+        ("'it''s'", "'it''s'"),
+        (r"'a\'b\\c\nd\te\x'", r"'a''b\\c\nd\tex'"),
+        ("1.50, .5, 0xFF_FF", "1.50, .5, 0xFF_FF"),
+        ("true, FALSE, null", "TRUE, FALSE, NULL"),
+    ];
+    for (input, output) in cases {
+        let ast = parse_query(&format!("SELECT {input}")).unwrap();
+        assert_eq!(ast.to_string(), format!("SELECT {output}"));
+        assert_eq!(parse_query(&ast.to_string()).unwrap(), ast, "{output}");
     }
 }
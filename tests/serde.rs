@@ -0,0 +1,89 @@
+#![cfg(feature = "serde")]
+
+use sqlparser_nom::ast::statement::{SelectStatement, Statement};
+use sqlparser_nom::{parse_query, parse_statements_with_recovery};
+
+#[test]
+pub fn test_round_trip() {
+    let cases = [
+        "SELECT 1",
+        "SELECT DISTINCT a, b AS c, * EXCLUDE (d) EXCEPT (e) FROM db.t AS x WHERE a > 1 AND NOT EXISTS (SELECT 1)",
+        "WITH RECURSIVE x (a, b) AS (SELECT a, b FROM t) SELECT a FROM x ORDER BY a DESC NULLS FIRST, b LIMIT 1 OFFSET 2",
+        "SELECT * FROM (a JOIN b ON a.x = b.x) LEFT JOIN (SELECT * FROM c) AS c ON c.y IN (1, 2) CROSS JOIN d",
+        "SELECT count(DISTINCT a), sum(b) OVER w, rank() OVER (PARTITION BY c ORDER BY d) FROM t \
+         GROUP BY a HAVING count(*) > 1 WINDOW w AS (ORDER BY e)",
+        "SELECT -a, +b FROM t WHERE c NOT IN (SELECT c FROM u)",
+        "SELECT 'a''b', 1.5, TRUE, NULL",
+        "SELECT a IS NOT NULL, b BETWEEN 1 AND 2, c LIKE ANY ('x%') ESCAPE '!', \
+         CASE d WHEN 1 THEN 'one' ELSE 'many' END",
+        "SELECT CAST(a AS STRUCT<x INT, y MAP<TEXT, DATE[]>>), b::VARCHAR(3), SAFE_CAST(c AS my_type)",
+        "SELECT a FROM t UNION ALL (SELECT a FROM u LIMIT 1) INTERSECT SELECT b FROM v ORDER BY a",
+        "SELECT * FROM (VALUES (1, 'a'), (2, NULL)) AS v (x, y) UNION VALUES (3, 'c')",
+        "SELECT string_agg(a, ',' ORDER BY a LIMIT 3), percentile_cont(0.5) WITHIN GROUP (ORDER BY b) \
+         FILTER (WHERE c > 0), first_value(d IGNORE NULLS) OVER (ORDER BY e) FROM t",
+        "SELECT sum(a) OVER (w ORDER BY b RANGE BETWEEN INTERVAL '1' DAY PRECEDING AND CURRENT ROW EXCLUDE TIES) \
+         FROM t WINDOW w AS (PARTITION BY c)",
+    ];
+    for sql in cases {
+        let stmt = parse_query(sql).unwrap();
+        let json = serde_json::to_string(&stmt).unwrap();
+        let deserialized: SelectStatement = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, stmt, "{sql}");
+        assert_eq!(deserialized.span.range(), stmt.span.range());
+        // node equality ignores spans, the debug output shows all of them
+        assert_eq!(format!("{deserialized:?}"), format!("{stmt:?}"));
+    }
+
+    let (stmts, _) = parse_statements_with_recovery("SELECT a +; WITH; SELECT 1");
+    let json = serde_json::to_string(&stmts).unwrap();
+    let deserialized: Vec<Statement> = serde_json::from_str(&json).unwrap();
+    assert_eq!(deserialized, stmts);
+}
+
+#[test]
+pub fn test_json_shape() {
+    let stmt = parse_query("SELECT a + 1 FROM t").unwrap();
+    let json = serde_json::to_value(&stmt.body).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "type": "Select",
+            "distinct": false,
+            "projection": [{
+                "type": "UnnamedExpr",
+                "value": {
+                    "type": "BinaryOp",
+                    "left": {
+                        "type": "ColumnRef",
+                        "database": null,
+                        "table": null,
+                        "column": { "value": "a", "span": { "start": 7, "end": 8 } },
+                        "span": { "start": 7, "end": 8 },
+                    },
+                    "op": "Add",
+                    "right": {
+                        "type": "Literal",
+                        "literal": { "type": "Number", "value": "1" },
+                        "span": { "start": 11, "end": 12 },
+                    },
+                    "span": { "start": 7, "end": 12 },
+                },
+            }],
+            "from": {
+                "type": "BaseTable",
+                "name": {
+                    "database": null,
+                    "table": { "value": "t", "span": { "start": 18, "end": 19 } },
+                    "span": { "start": 18, "end": 19 },
+                },
+                "alias": null,
+                "span": { "start": 18, "end": 19 },
+            },
+            "selection": null,
+            "group_by": [],
+            "having": null,
+            "named_windows": [],
+            "span": { "start": 0, "end": 19 },
+        })
+    );
+}